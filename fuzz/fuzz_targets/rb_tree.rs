@@ -0,0 +1,9 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use aisd_tree::differential::fuzz_one;
+use aisd_tree::rb_tree::BinTree;
+
+// cargo fuzz run rb_tree
+fuzz_target!(|data: &[u8]| fuzz_one::<BinTree<usize>>(data));
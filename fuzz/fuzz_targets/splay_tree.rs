@@ -0,0 +1,9 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use aisd_tree::differential::fuzz_one;
+use aisd_tree::splay_tree::SplayTree;
+
+// cargo fuzz run splay_tree
+fuzz_target!(|data: &[u8]| fuzz_one::<SplayTree<usize>>(data));
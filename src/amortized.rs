@@ -0,0 +1,89 @@
+use crate::chart::{draw_chart, ChartError, ChartOptions};
+use crate::experiment::Stats;
+use crate::splay_tree::{SplayTree, Step};
+use crate::workload::{cell_seed, Workload};
+
+// amortized cost of one splay operation is at most 3 log2(n) + 1 rotations
+pub fn bound(n: usize) -> f64 {
+    3.0 * (n as f64).log2() + 1.0
+}
+
+// per-phase accounting over all operations of all repetitions for one n
+#[derive(Clone, Debug, Default)]
+pub struct Summary {
+    pub count: usize,
+    pub rotations: usize,
+    pub max_rotations: usize,
+    pub potential_change: f64,
+    pub amortized: f64,
+    pub max_amortized: f64,
+}
+
+impl Summary {
+    fn add(&mut self, step: Step) {
+        self.count += 1;
+        self.rotations += step.rotations;
+        self.max_rotations = self.max_rotations.max(step.rotations);
+        self.potential_change += step.potential_change;
+        self.amortized += step.amortized();
+        self.max_amortized = self.max_amortized.max(step.amortized());
+    }
+
+    pub fn avg_rotations(&self) -> f64 {
+        self.rotations as f64 / self.count as f64
+    }
+
+    pub fn avg_potential_change(&self) -> f64 {
+        self.potential_change / self.count as f64
+    }
+
+    pub fn avg_amortized(&self) -> f64 {
+        self.amortized / self.count as f64
+    }
+}
+
+pub struct Row {
+    pub n: usize,
+    pub insert: Summary,
+    pub delete: Summary,
+}
+
+// the potential is measured by walking the tree around every operation, keep n modest
+pub fn sweep(workload: Workload, sizes: impl Iterator<Item=usize>, reps: usize, seed: u64) -> Vec<Row> {
+    sizes.map(|n| {
+        let mut row = Row { n, insert: Summary::default(), delete: Summary::default() };
+        for rep in 0..reps {
+            let sequence = workload.generate(n, cell_seed(seed, n, rep));
+            let mut tree = SplayTree::tracked();
+            for &x in &sequence.inserts {
+                tree.insert(x, &mut Stats::new());
+                row.insert.add(tree.last_step().unwrap());
+            }
+            for &x in &sequence.deletes {
+                tree.delete(x, &mut Stats::new());
+                row.delete.add(tree.last_step().unwrap());
+            }
+        }
+        row
+    }).collect()
+}
+
+pub fn draw_amortized(name: &str, rows: &[Row], options: &ChartOptions) -> Result<(), ChartError> {
+    let names = vec!["avg rotations", "max rotations", "avg amortized", "max amortized", "3 log2 n + 1"];
+    for phase in ["insert", "delete"] {
+        let summaries = rows.iter()
+            .map(|row| if phase == "insert" { &row.insert } else { &row.delete })
+            .collect::<Vec<_>>();
+        let lines = vec![
+            summaries.iter().map(|s| s.avg_rotations()).collect(),
+            summaries.iter().map(|s| s.max_rotations as f64).collect(),
+            summaries.iter().map(|s| s.avg_amortized()).collect(),
+            summaries.iter().map(|s| s.max_amortized).collect(),
+            rows.iter().map(|row| bound(row.n)).collect::<Vec<_>>(),
+        ];
+        let sizes = rows.iter().map(|row| row.n).collect::<Vec<_>>();
+        let options = options.clone().y_label("rotations");
+        draw_chart(lines, names.clone(), sizes.into_iter(), &format!("{}_amortized_{}", name, phase), &options, |_, y| y)?;
+    }
+    Ok(())
+}
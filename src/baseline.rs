@@ -0,0 +1,170 @@
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::experiment::Data;
+use crate::results::git_revision;
+use crate::runner::{run_cell, Measure};
+use crate::structure::StructureKind;
+use crate::workload::{cell_seed, Workload};
+
+// the canonical runs: our own trees on every workload, one repetition per size
+pub const SEED: u64 = 20240601;
+pub const SIZES: [usize; 3] = [100, 1_000, 5_000];
+
+// operation counts of a fixed set of seeded runs, they only move when a tree's code changes
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Baseline {
+    pub seed: u64,
+    #[serde(default)]
+    pub revision: Option<String>,
+    pub entries: Vec<Entry>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Entry {
+    pub structure: StructureKind,
+    pub workload: Workload,
+    pub n: usize,
+    pub phase: String,
+    pub counts: Counts,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Counts {
+    pub comps: usize,
+    pub ptr_read: usize,
+    pub ptr_swap: usize,
+    pub max_comps: usize,
+    pub max_ptr_read: usize,
+    pub max_ptr_swap: usize,
+}
+
+// one count that moved further than the tolerance, or a run present on only one side
+#[derive(Clone, Debug, PartialEq)]
+pub enum Mismatch {
+    Count { run: String, field: &'static str, expected: usize, actual: usize },
+    Missing(String),
+    Extra(String),
+}
+
+impl Counts {
+    pub const FIELDS: [&'static str; 6] = ["comps", "ptr_read", "ptr_swap", "max_comps", "max_ptr_read", "max_ptr_swap"];
+
+    fn from_data(data: &Data) -> Self {
+        // by name, so new Data fields don't shift what is read here
+        let fields = data.to_fields();
+        let field = |name: &str| fields[Data::FIELDS.iter().position(|&f| f == name).unwrap()];
+        Counts {
+            comps: field("sum_comps"),
+            ptr_read: field("sum_ptr_read"),
+            ptr_swap: field("sum_ptr_swap"),
+            max_comps: field("max_comps"),
+            max_ptr_read: field("max_ptr_read"),
+            max_ptr_swap: field("max_ptr_swap"),
+        }
+    }
+
+    fn values(&self) -> [usize; 6] {
+        [self.comps, self.ptr_read, self.ptr_swap, self.max_comps, self.max_ptr_read, self.max_ptr_swap]
+    }
+}
+
+impl Entry {
+    fn run(&self) -> String {
+        format!("{} {} n = {} {}", self.structure, self.workload, self.n, self.phase)
+    }
+}
+
+impl Baseline {
+    pub fn canonical() -> Self {
+        let runs = StructureKind::TREES.iter()
+            .flat_map(|&structure| Workload::ALL.iter().flat_map(move |&workload| {
+                SIZES.iter().map(move |&n| (structure, workload, n))
+            }))
+            .collect::<Vec<_>>();
+        Self::record(&runs, SEED)
+    }
+
+    // counts only, no heights or clocks, so the same runs always give the same numbers
+    pub fn record(runs: &[(StructureKind, Workload, usize)], seed: u64) -> Self {
+        let mut entries = Vec::new();
+        for &(structure, workload, n) in runs {
+            let sequence = workload.generate(n, cell_seed(seed, n, 0));
+            let (insert, delete) = run_cell(structure, &sequence, Measure::default());
+            for (phase, data) in [("insert", insert), ("delete", delete)] {
+                entries.push(Entry { structure, workload, n, phase: phase.to_string(), counts: Counts::from_data(&data) });
+            }
+        }
+        Baseline { seed, revision: git_revision(), entries }
+    }
+
+    // the same runs again on the current code
+    pub fn rerun(&self) -> Self {
+        let mut runs = Vec::new();
+        for entry in &self.entries {
+            let run = (entry.structure, entry.workload, entry.n);
+            if !runs.contains(&run) {
+                runs.push(run);
+            }
+        }
+        Self::record(&runs, self.seed)
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let file = fs::File::open(path)?;
+        Ok(serde_json::from_reader(io::BufReader::new(file))?)
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let file = fs::File::create(path)?;
+        serde_json::to_writer_pretty(io::BufWriter::new(file), self)?;
+        Ok(())
+    }
+
+    // tolerance is relative, 0.01 lets every count drift by 1%
+    pub fn compare(&self, current: &Baseline, tolerance: f64) -> Vec<Mismatch> {
+        let mut mismatches = Vec::new();
+        let find = |entries: &[Entry], entry: &Entry| {
+            entries.iter().find(|e| {
+                e.structure == entry.structure && e.workload == entry.workload && e.n == entry.n && e.phase == entry.phase
+            }).map(|e| e.counts)
+        };
+
+        for entry in &self.entries {
+            let Some(actual) = find(&current.entries, entry) else {
+                mismatches.push(Mismatch::Missing(entry.run()));
+                continue;
+            };
+            let fields = Counts::FIELDS.iter().zip(entry.counts.values().into_iter().zip(actual.values()));
+            for (&field, (expected, actual)) in fields {
+                let allowed = expected as f64 * tolerance;
+                if (actual as f64 - expected as f64).abs() > allowed {
+                    mismatches.push(Mismatch::Count { run: entry.run(), field, expected, actual });
+                }
+            }
+        }
+        for entry in &current.entries {
+            if find(&self.entries, entry).is_none() {
+                mismatches.push(Mismatch::Extra(entry.run()));
+            }
+        }
+        mismatches
+    }
+}
+
+impl Display for Mismatch {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            Mismatch::Count { run, field, expected, actual } => {
+                let change = (*actual as f64 - *expected as f64) / (*expected).max(1) as f64 * 100.0;
+                write!(f, "{}: {} {} -> {} ({:+.2}%)", run, field, expected, actual, change)
+            }
+            Mismatch::Missing(run) => write!(f, "{}: missing from the new run", run),
+            Mismatch::Extra(run) => write!(f, "{}: not in the baseline", run),
+        }
+    }
+}
@@ -0,0 +1,681 @@
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::{Args, Parser, Subcommand};
+
+use aisd_tree::{amortized, bin_tree, rb_tree, splay_tree};
+use aisd_tree::baseline::Baseline;
+use aisd_tree::chart::{stdout_color, ChartOptions, Format, Normalize};
+use aisd_tree::config::{Config, Outputs, SizeSweep};
+use aisd_tree::dot::{Annotation, DotOptions};
+use aisd_tree::experiment::{Metric, Stats};
+use aisd_tree::fit::{draw_fits, fit_all, Model};
+use aisd_tree::frames::{animate, draw_frames, draw_gif};
+use aisd_tree::printer::{Layout, PrintOptions, RedStyle};
+use aisd_tree::report::write_report;
+use aisd_tree::results::Saved;
+use aisd_tree::runner::{run, write_outputs};
+use aisd_tree::shape::Shape;
+use aisd_tree::structure::StructureKind;
+use aisd_tree::workload::Workload;
+
+#[derive(Parser)]
+#[command(name = "aisd", about = "Experiments on binary search trees")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run one tree (or a config file) over a size sweep
+    Run(RunArgs),
+    /// Run several trees on identical key sequences and compare them
+    Compare(CompareArgs),
+    /// Render charts from a json or csv results file written by `run`
+    Plot {
+        file: PathBuf,
+        /// Metrics to draw, all of them when omitted
+        #[arg(short, long, num_args = 1..)]
+        metrics: Vec<Metric>,
+        /// One chart per metric with a line per structure
+        #[arg(short, long)]
+        compare: bool,
+        #[command(flatten)]
+        chart: ChartArgs,
+    },
+    /// Draw a grid of all metrics per phase, one panel per metric and a line per structure,
+    /// with a <name>_index.md linking every chart
+    Report {
+        file: PathBuf,
+        /// Metrics to show, all of them when omitted
+        #[arg(short, long, num_args = 1..)]
+        metrics: Vec<Metric>,
+        #[command(flatten)]
+        chart: ChartArgs,
+    },
+    /// Fit growth models to each metric of a results file and chart the best fit against known bounds
+    Fit {
+        file: PathBuf,
+        /// Metrics to fit, the operation counts when omitted
+        #[arg(short, long, num_args = 1..)]
+        metrics: Vec<Metric>,
+        /// Only print the table
+        #[arg(long)]
+        no_charts: bool,
+        #[command(flatten)]
+        chart: ChartArgs,
+    },
+    /// Record the operation counts of the canonical seeded runs
+    Baseline {
+        #[arg(short, long, default_value = "baseline.json")]
+        out: PathBuf,
+    },
+    /// Rerun the runs of a saved baseline and fail when a count moved beyond the tolerance
+    Check {
+        #[arg(default_value = "baseline.json")]
+        file: PathBuf,
+        /// Allowed relative change of each count, 0.01 for 1%
+        #[arg(short, long, default_value_t = 0.0)]
+        tolerance: f64,
+    },
+    /// Track the splay tree potential and report actual and amortized costs per operation
+    Amortized(AmortizedArgs),
+    /// Compare the shapes the trees end up in, after the inserts and after half of the deletes
+    Shape {
+        /// Trees to compare, all three when omitted
+        #[arg(short, long, num_args = 1..)]
+        trees: Vec<StructureKind>,
+        #[arg(short, long, default_value = "random")]
+        workload: Workload,
+        #[arg(short, long, default_value_t = 10_000)]
+        n: usize,
+        #[arg(short, long)]
+        seed: Option<u64>,
+    },
+    /// Build a tree from the given keys and print it
+    Show {
+        tree: StructureKind,
+        keys: Vec<usize>,
+        /// Keys to delete after all inserts
+        #[arg(short, long, num_args = 1..)]
+        delete: Vec<usize>,
+        #[command(flatten)]
+        dot: DotArgs,
+        #[command(flatten)]
+        print: PrintArgs,
+    },
+    /// Build a tree from the given keys and draw every step of it, with the case applied as caption
+    Animate {
+        tree: StructureKind,
+        keys: Vec<usize>,
+        /// Keys to delete after all inserts
+        #[arg(short, long, num_args = 1..)]
+        delete: Vec<usize>,
+        /// Prefix of the frame file names
+        #[arg(short, long, default_value = "animation")]
+        name: String,
+        /// One animated gif instead of a file per frame
+        #[arg(long, conflicts_with_all = ["svg", "terminal"])]
+        gif: bool,
+        /// Time each gif frame stays up in milliseconds
+        #[arg(long, default_value_t = 1500, requires = "gif")]
+        delay: u32,
+        /// Write every frame as graphviz source instead of drawing it
+        #[arg(long, conflicts_with = "gif")]
+        dot: bool,
+        #[command(flatten)]
+        chart: ChartArgs,
+    },
+}
+
+#[derive(Args)]
+struct DotArgs {
+    /// Write the tree as graphviz source to this file, - for stdout
+    #[arg(long)]
+    dot: Option<PathBuf>,
+    /// Draw the empty child links
+    #[arg(long, requires = "dot")]
+    null_leaves: bool,
+    /// Draw the parent pointers, broken ones in red
+    #[arg(long, requires = "dot")]
+    parents: bool,
+    /// Values written under each key: depth, size, height or balance
+    #[arg(short, long, num_args = 1.., requires = "dot")]
+    annotate: Vec<Annotation>,
+}
+
+impl DotArgs {
+    fn options(&self) -> DotOptions {
+        self.annotate.iter().fold(
+            DotOptions::new().null_leaves(self.null_leaves).parents(self.parents),
+            |options, &annotation| options.annotate(annotation),
+        )
+    }
+
+    // stdout gets only the graph, so it can be piped into dot
+    fn write(&self, shape: Shape, print: impl FnOnce(&mut io::Stdout) -> io::Result<()>, to_dot: impl FnOnce(&DotOptions) -> String) -> Result<(), String> {
+        match &self.dot {
+            Some(path) if path.as_os_str() == "-" => print!("{}", to_dot(&self.options())),
+            Some(path) => {
+                fs::write(path, to_dot(&self.options())).map_err(|e| format!("cannot write {}: {}", path.display(), e))?;
+                print_tree(shape, print)?;
+            }
+            None => print_tree(shape, print)?,
+        }
+        Ok(())
+    }
+}
+
+#[derive(Args)]
+struct PrintArgs {
+    /// Draw the tree top-down instead of sideways
+    #[arg(long)]
+    vertical: bool,
+    /// Deepest level printed, with the root at 0; what hangs below is shown as …
+    #[arg(long)]
+    max_depth: Option<usize>,
+    /// Nodes printed in level order before the rest is shown as …
+    #[arg(long)]
+    max_nodes: Option<usize>,
+    /// How red nodes are marked: ansi, brackets or plain; ansi on a terminal unless NO_COLOR is set
+    #[arg(long)]
+    red: Option<RedStyle>,
+}
+
+impl PrintArgs {
+    fn options(&self) -> PrintOptions {
+        let color = stdout_color();
+        let red = self.red.unwrap_or(if color { RedStyle::Ansi } else { RedStyle::Brackets });
+        PrintOptions::new()
+            .red(red)
+            .layout(if self.vertical { Layout::Vertical } else { Layout::Sideways })
+            .max_depth(self.max_depth)
+            .max_nodes(self.max_nodes)
+    }
+}
+
+#[derive(Args)]
+struct RunArgs {
+    /// Experiment file (.toml or .json), which sets everything the other options would
+    #[arg(short, long, conflicts_with_all = [
+        "tree", "workload", "start", "end", "step", "reps", "timing", "warmup", "threads", "seed",
+        "name", "out", "svg", "terminal", "csv", "json", "no_charts", "checkpoint",
+    ])]
+    config: Option<PathBuf>,
+    #[arg(short, long, required_unless_present = "config")]
+    tree: Option<StructureKind>,
+    #[command(flatten)]
+    sweep: SweepArgs,
+}
+
+#[derive(Args)]
+struct CompareArgs {
+    /// Structures to compare, all of them (btree_set and rbtree_crate included) when omitted
+    #[arg(short, long, num_args = 1..)]
+    trees: Vec<StructureKind>,
+    /// Metric shown in the comparison table
+    #[arg(short, long, default_value = "avg_comps")]
+    metric: Metric,
+    #[command(flatten)]
+    sweep: SweepArgs,
+}
+
+#[derive(Args)]
+struct AmortizedArgs {
+    #[arg(short, long, default_value = "random")]
+    workload: Workload,
+    #[arg(long, default_value_t = 500)]
+    start: usize,
+    #[arg(long, default_value_t = 5_000)]
+    end: usize,
+    #[arg(long, default_value_t = 500)]
+    step: usize,
+    #[arg(short, long, default_value_t = 5)]
+    reps: usize,
+    #[arg(short, long)]
+    seed: Option<u64>,
+    /// Prefix of the chart names
+    #[arg(short, long, default_value = "splay")]
+    name: String,
+    #[arg(long)]
+    no_charts: bool,
+    #[command(flatten)]
+    chart: ChartArgs,
+}
+
+#[derive(Args)]
+struct ChartArgs {
+    /// Directory the charts are written to
+    #[arg(long, default_value = "charts")]
+    chart_dir: PathBuf,
+    /// Write svg instead of png
+    #[arg(long)]
+    svg: bool,
+    /// Print braille charts to stdout instead of writing images
+    #[arg(long, conflicts_with = "svg")]
+    terminal: bool,
+    /// Width of terminal charts in characters
+    #[arg(long, default_value_t = 80)]
+    columns: usize,
+    /// Height of the plot area of terminal charts in lines
+    #[arg(long, default_value_t = 20)]
+    rows: usize,
+    #[arg(long, default_value_t = 1280)]
+    width: u32,
+    #[arg(long, default_value_t = 720)]
+    height: u32,
+    #[arg(long, default_value = "Calibri")]
+    font: String,
+    #[arg(long)]
+    log_x: bool,
+    /// Log scale y axis, values at or below zero are left out
+    #[arg(long)]
+    log_y: bool,
+    /// Divide every value by log2_n, ln_n or n
+    #[arg(long)]
+    normalize: Option<Normalize>,
+    /// Shade averages up to their maximum and phase times by ±1 standard deviation
+    #[arg(long)]
+    bands: bool,
+}
+
+fn format(svg: bool, terminal: bool) -> Format {
+    match (svg, terminal) {
+        (true, _) => Format::Svg,
+        (_, true) => Format::Terminal,
+        _ => Format::Png,
+    }
+}
+
+impl ChartArgs {
+    fn options(self) -> ChartOptions {
+        ChartOptions::new()
+            .dir(self.chart_dir)
+            .format(format(self.svg, self.terminal))
+            .size(self.width, self.height)
+            .text_size(self.columns, self.rows)
+            .font(self.font)
+            .x_label("n")
+            .log_x(self.log_x)
+            .log_y(self.log_y)
+            .normalize(self.normalize)
+            .bands(self.bands)
+    }
+}
+
+#[derive(Args)]
+struct SweepArgs {
+    #[arg(short, long, default_value = "random")]
+    workload: Workload,
+    #[arg(long, default_value_t = 10_000)]
+    start: usize,
+    #[arg(long, default_value_t = 100_000)]
+    end: usize,
+    #[arg(long, default_value_t = 10_000)]
+    step: usize,
+    #[arg(short, long, default_value_t = 20)]
+    reps: usize,
+    /// Measure wall-clock time as well as the operation counts
+    #[arg(long)]
+    timing: bool,
+    /// Repetitions run before the measured ones and thrown away
+    #[arg(long, default_value_t = 0)]
+    warmup: usize,
+    /// Jobs run in parallel, 0 for one per core
+    #[arg(short = 'j', long, default_value_t = 1)]
+    threads: usize,
+    #[arg(short, long)]
+    seed: Option<u64>,
+    /// Prefix of the output file names
+    #[arg(short, long, default_value = "experiment")]
+    name: String,
+    /// Directory for csv, json and chart outputs
+    #[arg(short, long, default_value = "charts")]
+    out: PathBuf,
+    /// Write charts as svg instead of png
+    #[arg(long)]
+    svg: bool,
+    /// Print charts to stdout as braille instead of writing images
+    #[arg(long, conflicts_with = "svg")]
+    terminal: bool,
+    #[arg(long)]
+    csv: bool,
+    #[arg(long)]
+    json: bool,
+    #[arg(long)]
+    no_charts: bool,
+    /// Keep finished cells in this file and skip them when the same run is started again
+    #[arg(long)]
+    checkpoint: Option<PathBuf>,
+}
+
+impl SweepArgs {
+    fn config(self, structures: Vec<StructureKind>) -> Config {
+        let mut metrics = Metric::COUNTERS.to_vec();
+        if self.timing {
+            metrics.extend([Metric::AvgNanos, Metric::MaxNanos, Metric::PhaseNanos]);
+        }
+        Config {
+            name: self.name,
+            structures,
+            sizes: SizeSweep { start: self.start, end: self.end, step: self.step },
+            reps: self.reps,
+            warmup: self.warmup,
+            threads: self.threads,
+            workload: self.workload,
+            seed: self.seed,
+            metrics,
+            outputs: Outputs {
+                dir: self.out,
+                format: format(self.svg, self.terminal),
+                log_x: false,
+                log_y: false,
+                normalize: None,
+                bands: false,
+                charts: !self.no_charts,
+                comparison: false,
+                report: false,
+                csv: self.csv,
+                json: self.json,
+                checkpoint: self.checkpoint,
+            },
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    let result = match cli.command {
+        Command::Run(args) => run_command(args),
+        Command::Compare(args) => compare_command(args),
+        Command::Plot { file, metrics, compare, chart } => plot_command(file, metrics, compare, chart.options()),
+        Command::Report { file, metrics, chart } => report_command(file, metrics, chart.options()),
+        Command::Fit { file, metrics, no_charts, chart } => fit_command(file, metrics, no_charts, chart.options()),
+        Command::Baseline { out } => baseline_command(out),
+        Command::Check { file, tolerance } => check_command(file, tolerance),
+        Command::Amortized(args) => amortized_command(args),
+        Command::Shape { trees, workload, n, seed } => shape_command(trees, workload, n, seed),
+        Command::Show { tree, keys, delete, dot, print } => show_command(tree, keys, delete, dot, print.options()),
+        Command::Animate { tree, keys, delete, name, gif, delay, dot, chart } =>
+            animate_command(tree, keys, delete, name, gif.then_some(delay), dot, chart.options()),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run_command(args: RunArgs) -> Result<(), String> {
+    let config = match args.config {
+        Some(path) => Config::load(&path).map_err(|e| e.to_string())?,
+        None => args.sweep.config(args.tree.into_iter().collect()),
+    };
+    config.validate().map_err(|e| e.to_string())?;
+
+    let outcome = run(&config).map_err(|e| format!("run failed: {}", e))?;
+    println!("seed: {}", outcome.seed);
+    write_outputs(&config, &outcome).map_err(|e| format!("cannot write outputs: {}", e))
+}
+
+fn compare_command(args: CompareArgs) -> Result<(), String> {
+    let trees = if args.trees.is_empty() { StructureKind::ALL.to_vec() } else { args.trees };
+    let metric = args.metric;
+    let mut config = args.sweep.config(trees);
+    if !config.metrics.contains(&metric) {
+        config.metrics.push(metric);
+    }
+    // overlaid charts instead of one chart per structure
+    config.outputs.comparison = config.outputs.charts;
+    config.outputs.charts = false;
+    config.validate().map_err(|e| e.to_string())?;
+
+    let outcome = run(&config).map_err(|e| format!("run failed: {}", e))?;
+    println!("seed: {}", outcome.seed);
+
+    for phase in ["insert", "delete"] {
+        println!("\n{} {}", phase, metric.name());
+        print!("{:>10}", "n");
+        for results in &outcome.results {
+            print!("{:>14}", results.structure.name());
+        }
+        println!();
+
+        for (i, n) in config.sizes.iter().enumerate() {
+            print!("{:>10}", n);
+            for results in &outcome.results {
+                let data = if phase == "insert" { &results.insert[i] } else { &results.delete[i] };
+                if metric.tree_only() && !results.structure.is_tree() {
+                    print!("{:>14}", "-");
+                } else {
+                    print!("{:>14.3}", metric.value(data));
+                }
+            }
+            println!();
+        }
+    }
+
+    write_outputs(&config, &outcome).map_err(|e| format!("cannot write outputs: {}", e))
+}
+
+fn plot_command(file: PathBuf, metrics: Vec<Metric>, compare: bool, options: ChartOptions) -> Result<(), String> {
+    let saved = Saved::load(&file).map_err(|e| format!("cannot load {}: {}", file.display(), e))?;
+    let metrics = if metrics.is_empty() { Metric::ALL.to_vec() } else { metrics };
+
+    let meta = &saved.metadata;
+    println!("{}: workload {}, seed {}, reps {}, revision {}",
+             meta.name, meta.workload, meta.seed, meta.reps, meta.revision.as_deref().unwrap_or("unknown"));
+    if compare {
+        saved.draw_comparison(&metrics, &options)
+    } else {
+        saved.draw_charts(&metrics, &options)
+    }.map_err(|e| e.to_string())
+}
+
+fn report_command(file: PathBuf, metrics: Vec<Metric>, options: ChartOptions) -> Result<(), String> {
+    let saved = Saved::load(&file).map_err(|e| format!("cannot load {}: {}", file.display(), e))?;
+    let metrics = if metrics.is_empty() { Metric::ALL.to_vec() } else { metrics };
+    let index = write_report(&saved, &metrics, &options).map_err(|e| format!("cannot write report: {}", e))?;
+    println!("{}", index.display());
+    Ok(())
+}
+
+fn fit_command(file: PathBuf, metrics: Vec<Metric>, no_charts: bool, options: ChartOptions) -> Result<(), String> {
+    let saved = Saved::load(&file).map_err(|e| format!("cannot load {}: {}", file.display(), e))?;
+    let metrics = if metrics.is_empty() { Metric::COUNTERS.to_vec() } else { metrics };
+    let reports = fit_all(&saved, &metrics);
+
+    print!("{:<12}{:<8}{:<14}{:<12}{:>12}{:>12}", "structure", "phase", "metric", "best", "a", "b");
+    for model in Model::ALL {
+        print!("{:>12}", format!("R2 {}", model.name()));
+    }
+    println!();
+    for report in &reports {
+        print!("{:<12}{:<8}{:<14}", report.structure.name(), report.phase, report.metric.key());
+        match &report.best {
+            Some(best) => print!("{:<12}{:>12.4}{:>12.4}", best.model.name(), best.a, best.b),
+            None => print!("{:<12}{:>12}{:>12}", "no good fit", "-", "-"),
+        }
+        for model in Model::ALL {
+            match report.fits.iter().find(|fit| fit.model == model) {
+                Some(fit) => print!("{:>12.5}", fit.r2),
+                None => print!("{:>12}", "-"),
+            }
+        }
+        println!();
+    }
+
+    if !no_charts {
+        draw_fits(&saved, &reports, &options).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+fn baseline_command(out: PathBuf) -> Result<(), String> {
+    let baseline = Baseline::canonical();
+    baseline.save(&out).map_err(|e| format!("cannot write {}: {}", out.display(), e))?;
+    println!("{} counts of seed {} saved to {}", baseline.entries.len(), baseline.seed, out.display());
+    Ok(())
+}
+
+fn check_command(file: PathBuf, tolerance: f64) -> Result<(), String> {
+    let baseline = Baseline::load(&file).map_err(|e| format!("cannot load {}: {}", file.display(), e))?;
+    let mismatches = baseline.compare(&baseline.rerun(), tolerance);
+    if mismatches.is_empty() {
+        println!("{} counts match {} (revision {})",
+                 baseline.entries.len(), file.display(), baseline.revision.as_deref().unwrap_or("unknown"));
+        return Ok(());
+    }
+    for mismatch in &mismatches {
+        println!("{}", mismatch);
+    }
+    Err(format!("{} counts differ from {}", mismatches.len(), file.display()))
+}
+
+fn amortized_command(args: AmortizedArgs) -> Result<(), String> {
+    if args.step == 0 || args.start == 0 || args.start > args.end || args.reps == 0 {
+        return Err("bad size sweep or repetitions".to_string());
+    }
+    let seed = args.seed.unwrap_or_else(rand::random);
+    println!("seed: {}", seed);
+    let rows = amortized::sweep(args.workload, (args.start..=args.end).step_by(args.step), args.reps, seed);
+
+    for phase in ["insert", "delete"] {
+        println!("\n{}", phase);
+        println!("{:>10}{:>14}{:>14}{:>14}{:>14}{:>14}{:>14}",
+                 "n", "avg rot", "max rot", "avg dPhi", "avg amort", "max amort", "3 log n + 1");
+        for row in &rows {
+            let summary = if phase == "insert" { &row.insert } else { &row.delete };
+            println!("{:>10}{:>14.3}{:>14}{:>14.3}{:>14.3}{:>14.3}{:>14.3}",
+                     row.n, summary.avg_rotations(), summary.max_rotations, summary.avg_potential_change(),
+                     summary.avg_amortized(), summary.max_amortized, amortized::bound(row.n));
+        }
+    }
+
+    if !args.no_charts {
+        amortized::draw_amortized(&args.name, &rows, &args.chart.options()).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+// a row of the shape table: its label and how to print it for one tree
+type ShapeRow = (&'static str, fn(&Shape) -> String);
+
+fn shape_command(trees: Vec<StructureKind>, workload: Workload, n: usize, seed: Option<u64>) -> Result<(), String> {
+    let trees = if trees.is_empty() { StructureKind::TREES.to_vec() } else { trees };
+    if let Some(tree) = trees.iter().find(|tree| !tree.is_tree()) {
+        return Err(format!("{} does not expose its shape", tree));
+    }
+    if n == 0 {
+        return Err("n must be at least 1".to_string());
+    }
+    let seed = seed.unwrap_or_else(rand::random);
+    println!("seed: {}", seed);
+
+    let sequence = workload.generate(n, seed);
+    let stats = &mut Stats::new();
+    let mut built = trees.iter().map(|tree| tree.build()).collect::<Vec<_>>();
+    for tree in built.iter_mut() {
+        sequence.inserts.iter().for_each(|&x| tree.insert(x, stats));
+    }
+    let after_inserts = built.iter().map(|tree| tree.shape().unwrap()).collect::<Vec<_>>();
+    for tree in built.iter_mut() {
+        sequence.deletes[..n / 2].iter().for_each(|&x| tree.delete(x, stats));
+    }
+    let after_deletes = built.iter().map(|tree| tree.shape().unwrap()).collect::<Vec<_>>();
+
+    for (stage, shapes) in [("after inserts", after_inserts), ("after half of the deletes", after_deletes)] {
+        println!("\n{}", stage);
+        print!("{:<24}", "");
+        trees.iter().for_each(|tree| print!("{:>14}", tree.name()));
+        println!();
+
+        let rows: [ShapeRow; 10] = [
+            ("size", |s| s.size.to_string()),
+            ("height", |s| s.height.to_string()),
+            ("min leaf depth", |s| s.min_leaf_depth.to_string()),
+            ("avg depth", |s| format!("{:.3}", s.avg_depth())),
+            ("internal path length", |s| s.internal_path_length().to_string()),
+            ("external path length", |s| s.external_path_length.to_string()),
+            ("height / log2(n+1)", |s| format!("{:.3}", s.height_ratio())),
+            ("|balance factor| <= 1", |s| format!("{:.1}%", s.avl_balanced() * 100.0)),
+            ("max |balance factor|", |s| {
+                s.balance_factors.keys().map(|bf| bf.unsigned_abs()).max().unwrap_or(0).to_string()
+            }),
+            ("black height", |s| s.black_height.map_or("-".to_string(), |bh| bh.to_string())),
+        ];
+        for (name, value) in rows {
+            print!("{:<24}", name);
+            shapes.iter().for_each(|shape| print!("{:>14}", value(shape)));
+            println!();
+        }
+    }
+    Ok(())
+}
+
+fn show_command(tree: StructureKind, keys: Vec<usize>, delete: Vec<usize>, dot: DotArgs, print: PrintOptions) -> Result<(), String> {
+    let stats = &mut Stats::new();
+    match tree {
+        StructureKind::BinTree => {
+            let mut tree = bin_tree::BinTree::new();
+            keys.into_iter().for_each(|x| tree.insert(x, stats));
+            delete.into_iter().for_each(|x| { tree.delete(x, stats); });
+            dot.write(tree.shape(), |out| tree.print(out, &print), |options| tree.to_dot(options))?;
+        }
+        StructureKind::RbTree => {
+            let mut tree = rb_tree::BinTree::new();
+            keys.into_iter().for_each(|x| tree.insert(x, stats));
+            delete.into_iter().for_each(|x| { tree.delete(x, stats); });
+            dot.write(tree.shape(), |out| tree.print(out, &print), |options| tree.to_dot(options))?;
+        }
+        StructureKind::SplayTree => {
+            let mut tree = splay_tree::SplayTree::new();
+            keys.into_iter().for_each(|x| tree.insert(x, stats));
+            delete.into_iter().for_each(|x| { tree.delete(x, stats); });
+            dot.write(tree.shape(), |out| tree.print(out, &print), |options| tree.to_dot(options))?;
+        }
+        StructureKind::BTreeSet | StructureKind::RbTreeCrate => {
+            return Err(format!("{} has no tree printer", tree));
+        }
+    }
+    Ok(())
+}
+
+fn animate_command(tree: StructureKind, keys: Vec<usize>, delete: Vec<usize>, name: String, gif: Option<u32>, dot: bool, options: ChartOptions) -> Result<(), String> {
+    let frames = animate(tree, &keys, &delete).ok_or_else(|| format!("{} cannot be animated", tree))?;
+    println!("{} frames", frames.len());
+    if dot {
+        let dir = options.out_dir();
+        fs::create_dir_all(dir).map_err(|e| format!("cannot create {}: {}", dir.display(), e))?;
+        for (i, frame) in frames.iter().enumerate() {
+            let path = dir.join(format!("{}_{:03}.dot", name, i));
+            let source = format!("// {}\n{}", frame.caption, frame.tree.to_dot(&DotOptions::new()));
+            fs::write(&path, source).map_err(|e| format!("cannot write {}: {}", path.display(), e))?;
+            println!("{}", path.display());
+        }
+    } else if let Some(delay) = gif {
+        let path = draw_gif(&frames, &name, &options, delay).map_err(|e| e.to_string())?;
+        println!("{}", path.display());
+    } else {
+        for path in draw_frames(&frames, &name, &options).map_err(|e| e.to_string())? {
+            println!("{}", path.display());
+        }
+    }
+    Ok(())
+}
+
+fn print_tree(shape: Shape, print: impl FnOnce(&mut io::Stdout) -> io::Result<()>) -> Result<(), String> {
+    println!("height: {}", shape.height);
+    print(&mut io::stdout()).map_err(|e| format!("cannot print the tree: {}", e))?;
+    println!("depth    nodes");
+    println!("{}", shape.depths);
+    println!("{}", shape);
+    Ok(())
+}
@@ -1,10 +1,18 @@
 use std::cell::{RefCell, RefMut};
 use std::collections::VecDeque;
 use std::fmt::{Debug, Formatter};
-use std::rc::Rc;
+use std::io;
+use std::rc::{Rc, Weak};
+
+use itertools::Itertools;
 
 use crate::bin_tree::tree_node::{Node, NodePointer};
+use crate::dot::{to_dot, DotOptions};
 use crate::experiment::Stats;
+use crate::frames::{debug_key, Keys, Snapshot};
+use crate::printer::{print_tree, write_tree, PrintOptions};
+use crate::shape::{DepthHistogram, Shape};
+use crate::validate::{validate, InvariantError};
 
 mod tree_node;
 
@@ -13,11 +21,118 @@ pub struct BinTree<T: Ord> {
     size: usize,
 }
 
+pub struct Iter<T: Ord> {
+    stack: Vec<NodePointer<T>>,
+}
+
+impl<T: Ord> Iter<T> {
+    fn push_left(&mut self, mut node: Option<NodePointer<T>>) {
+        while let Some(current) = node {
+            node = current.borrow().left.clone();
+            self.stack.push(current);
+        }
+    }
+}
+
+impl<T: Ord + Clone> Iterator for Iter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        let node = self.stack.pop()?;
+        let node_ref = node.borrow();
+        self.push_left(node_ref.right.clone());
+        Some(node_ref.val.clone())
+    }
+}
+
+impl<T: Ord> Default for BinTree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<T: Ord> BinTree<T> {
     pub fn new() -> Self {
         BinTree { root: None, size: 0 }
     }
 
+    pub fn len(&self) -> usize {
+        self.size
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    // unlinks the nodes one at a time, dropping a long path in one go would recurse down all of it
+    pub fn clear(&mut self) {
+        let mut stack: Vec<NodePointer<T>> = self.root.take().into_iter().collect();
+        while let Some(node) = stack.pop() {
+            let mut node_ref = node.borrow_mut();
+            stack.extend(node_ref.left.take());
+            stack.extend(node_ref.right.take());
+        }
+        self.size = 0;
+    }
+
+    // perfectly balanced in O(n), the keys have to come in order, repeats allowed
+    pub fn from_sorted_iter(iter: impl IntoIterator<Item = T>) -> Self {
+        let nodes = iter.into_iter().map(|val| Node::new_pointer(val, Weak::new())).collect::<Vec<_>>();
+        assert!(nodes.is_sorted_by(|a, b| a.borrow().val <= b.borrow().val), "from_sorted_iter got keys out of order");
+        Self::from_nodes(nodes)
+    }
+
+    fn from_nodes(nodes: Vec<NodePointer<T>>) -> Self {
+        let size = nodes.len();
+        let root = build(&mut nodes.into_iter(), size);
+        if let Some(root) = &root {
+            root.borrow_mut().parent = Rc::downgrade(root);
+        }
+        let tree = BinTree { root, size };
+        #[cfg(feature = "check-invariants")]
+        tree.check("from_sorted_iter", None);
+        tree
+    }
+
+    // the nodes in order, unlinked from each other, leaving the tree empty
+    fn take_nodes(&mut self) -> Vec<NodePointer<T>> {
+        let mut nodes = Vec::with_capacity(self.size);
+        let mut stack = Vec::new();
+        let mut current = self.root.take();
+        loop {
+            while let Some(node) = current {
+                current = node.borrow_mut().left.take();
+                stack.push(node);
+            }
+            let Some(node) = stack.pop() else { break };
+            current = node.borrow_mut().right.take();
+            nodes.push(node);
+        }
+        self.size = 0;
+        nodes
+    }
+
+    // a plain search, nothing is counted
+    pub fn contains(&self, val: &T) -> bool {
+        let mut current = self.root.clone();
+        while let Some(node) = current {
+            let node_ref = node.borrow();
+            current = match Ord::cmp(val, &node_ref.val) {
+                std::cmp::Ordering::Less => node_ref.left.clone(),
+                std::cmp::Ordering::Greater => node_ref.right.clone(),
+                std::cmp::Ordering::Equal => return true,
+            };
+        }
+        false
+    }
+
+    // the keys in order, cloned out of the nodes
+    pub fn iter(&self) -> Iter<T> where T: Clone {
+        let mut iter = Iter { stack: Vec::new() };
+        iter.push_left(self.root.clone());
+        iter
+    }
+
     pub fn height(&self) -> usize {
         match &self.root {
             None => 0,
@@ -25,6 +140,14 @@ impl<T: Ord> BinTree<T> {
         }
     }
 
+    pub fn depths(&self) -> DepthHistogram {
+        DepthHistogram::of(self.root.clone())
+    }
+
+    pub fn shape(&self) -> Shape {
+        Shape::of(self.root.clone())
+    }
+
     pub fn height2(&self) -> usize {
         match &self.root {
             None => 0,
@@ -53,6 +176,10 @@ impl<T: Ord> BinTree<T> {
     }
 
     pub fn insert(&mut self, val: T, stats: &mut Stats) {
+        self.insert_keyed(val, stats, None);
+    }
+
+    fn insert_keyed(&mut self, val: T, stats: &mut Stats, keys: Keys<T>) {
         self.size += 1;
         match &self.root {
             None => {
@@ -61,11 +188,14 @@ impl<T: Ord> BinTree<T> {
                     RefCell::new(Node::new(val, weak.clone()))
                 }));
                 self.size = 1;
+                self.frame(stats, keys, || "new root".to_string());
             }
             Some(root) => {
                 stats.read();
                 let mut current = root.clone();
+                let mut depth = 0;
                 loop {
+                    depth += 1;
                     current = {
                         let mut curr_ref = current.borrow_mut();
 
@@ -82,6 +212,7 @@ impl<T: Ord> BinTree<T> {
                                 child.clone()
                             }
                             None => {
+                                stats.depth(depth);
                                 stats.swap();
                                 *child = Some(Node::new_pointer(val, Rc::downgrade(&current)));
                                 break;
@@ -89,13 +220,79 @@ impl<T: Ord> BinTree<T> {
                         }
                     }
                 }
+                self.frame(stats, keys, || format!("new leaf at depth {}", depth));
             }
         }
+        #[cfg(feature = "check-invariants")]
+        self.check("insert", keys);
     }
 
     pub fn delete(&mut self, val: T, stats: &mut Stats) -> bool {
+        self.delete_keyed(val, stats, None)
+    }
+
+    fn delete_keyed(&mut self, val: T, stats: &mut Stats, keys: Keys<T>) -> bool {
+        // worked out up front, the node is gone once remove is done
+        let caption = keys.filter(|_| stats.is_recording()).and_then(|keys| self.delete_case(&val, keys));
+        let removed = self.remove(val, stats);
+        if let Some(caption) = caption {
+            self.frame(stats, keys, || caption);
+        }
+        #[cfg(feature = "check-invariants")]
+        self.check("delete", keys);
+        removed
+    }
+
+    // the whole tree after a step, only when there is a way to write the keys
+    fn frame(&self, stats: &mut Stats, keys: Keys<T>, caption: impl FnOnce() -> String) {
+        if let Some(keys) = keys {
+            stats.frame(caption, || Snapshot::of_with(self.root.clone(), |node| keys(&node.borrow().val)));
+        }
+    }
+
+    // keys are written by keys when there is one, otherwise nodes go by their address
+    #[cfg(feature = "check-invariants")]
+    fn check(&self, op: &str, keys: Keys<T>) {
+        let key = |node: &NodePointer<T>| match keys {
+            Some(keys) => keys(&node.borrow().val),
+            None => format!("node at {:#x}", crate::shape::NodeView::id(node)),
+        };
+        if let Err(e) = crate::validate::validate_with(self.root.clone(), Some(self.size), None, key) {
+            panic!("{} left the tree broken: {}", op, e);
+        }
+    }
+
+    // what removing val does to the tree, found without touching the counters
+    fn delete_case(&self, val: &T, keys: fn(&T) -> String) -> Option<String> {
+        let mut current = self.root.clone();
+        while let Some(node) = current {
+            let node_ref = node.borrow();
+            current = match Ord::cmp(val, &node_ref.val) {
+                std::cmp::Ordering::Less => node_ref.left.clone(),
+                std::cmp::Ordering::Greater => node_ref.right.clone(),
+                std::cmp::Ordering::Equal => {
+                    let case = match (&node_ref.left, &node_ref.right) {
+                        (None, None) => "delete: leaf removed".to_string(),
+                        (Some(_), None) | (None, Some(_)) => "delete: node replaced by its only child".to_string(),
+                        (Some(_), Some(right)) => {
+                            let mut successor = right.clone();
+                            while let Some(left) = successor.clone().borrow().left.clone() {
+                                successor = left;
+                            }
+                            let successor = keys(&successor.borrow().val);
+                            format!("delete: node replaced by its successor {}", successor)
+                        }
+                    };
+                    return Some(case);
+                }
+            };
+        }
+        None
+    }
+
+    fn remove(&mut self, val: T, stats: &mut Stats) -> bool {
         if self.root.is_none() {
-            return false;
+            false
         } else {
             // handling case when root is to be deleted
             let mut comp;
@@ -112,12 +309,9 @@ impl<T: Ord> BinTree<T> {
                     stats.swap();
                     self.root = new;
 
-                    match &self.root {
-                        Some(new) => {
-                            stats.swap();
-                            new.borrow_mut().parent = Rc::downgrade(&self.root.as_ref().unwrap());
-                        }
-                        _ => {}
+                    if let Some(new) = &self.root {
+                        stats.swap();
+                        new.borrow_mut().parent = Rc::downgrade(self.root.as_ref().unwrap());
                     }
                     self.size -= 1;
                     return true;
@@ -127,6 +321,7 @@ impl<T: Ord> BinTree<T> {
             //when root is not the one to be deleted
             stats.read();
             let mut current = self.root.clone().unwrap();
+            let mut depth = 0;
             loop {
                 current = {
                     let mut current_ref = current.borrow_mut();
@@ -144,6 +339,8 @@ impl<T: Ord> BinTree<T> {
                         }
                         Some(child_ptr) => {
                             let child_ref = child_ptr.borrow_mut();
+                            depth += 1;
+                            stats.depth(depth);
 
                             stats.comp();
                             //comparing values
@@ -291,34 +488,82 @@ fn get_replacement<T: Ord>(mut node: RefMut<Node<T>>, stats: &mut Stats) -> Opti
 
     new
 }
+impl<T: Ord + Debug> BinTree<T> {
+    // insert and delete that record a frame of each step when stats is recording
+    pub fn insert_framed(&mut self, val: T, stats: &mut Stats) {
+        self.insert_keyed(val, stats, Some(debug_key));
+    }
+
+    pub fn delete_framed(&mut self, val: T, stats: &mut Stats) -> bool {
+        self.delete_keyed(val, stats, Some(debug_key))
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot::of(self.root.clone())
+    }
+
+    // graphviz source of the tree, render it with `dot -Tsvg`
+    pub fn to_dot(&self, options: &DotOptions) -> String {
+        to_dot(self.root.clone(), options)
+    }
+
+    // ordering, parent pointers and the size count
+    pub fn validate(&self) -> Result<(), InvariantError> {
+        validate(self.root.clone(), Some(self.size), None)
+    }
+
+    // drawn as text, see PrintOptions for the layouts and limits
+    pub fn print(&self, out: &mut impl io::Write, options: &PrintOptions) -> io::Result<()> {
+        print_tree(out, self.root.clone(), options)
+    }
+}
+
 impl<T: Debug + Ord> Debug for BinTree<T> {
     fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
-        if let Some(root) = &self.root {
-            root.borrow().print_node(f, String::new());
+        write_tree(f, self.root.clone(), &PrintOptions::default())
+    }
+}
+
+// sorted input at least as long as the tree is merged into it in one pass and the whole
+// tree rebuilt, anything else goes in one insert at a time
+impl<T: Ord> Extend<T> for BinTree<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let vals = iter.into_iter().collect::<Vec<_>>();
+        if vals.is_sorted() && vals.len() >= self.size {
+            let new = vals.into_iter().map(|val| Node::new_pointer(val, Weak::new()));
+            let nodes = self.take_nodes().into_iter().merge_by(new, |a, b| a.borrow().val <= b.borrow().val).collect();
+            *self = Self::from_nodes(nodes);
         } else {
-            write!(f, "Empty tree")?;
+            let stats = &mut Stats::new();
+            vals.into_iter().for_each(|val| self.insert(val, stats));
         }
-        Ok(())
     }
 }
 
-fn max_from<T: Ord>(start: NodePointer<T>, stats: &mut Stats) -> NodePointer<T> {
-    let mut current = start;
-    // while let Some(right) = current.clone().borrow().right.clone() {
-    //     current = right;
-    // }
+impl<T: Ord> FromIterator<T> for BinTree<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut tree = Self::new();
+        tree.extend(iter);
+        tree
+    }
+}
 
-    loop {
-        current = {
-            let current_ref = current.borrow();
-            stats.read();
-            match &current_ref.right {
-                Some(right) => right.clone(),
-                None => break,
-            }
-        }
+// the next n nodes as a balanced subtree, the middle one at its root
+fn build<T: Ord>(nodes: &mut impl Iterator<Item = NodePointer<T>>, n: usize) -> Option<NodePointer<T>> {
+    if n == 0 {
+        return None;
     }
-    current
+    let left = build(nodes, (n - 1) / 2);
+    let node = nodes.next().unwrap();
+    let right = build(nodes, n / 2);
+    for child in left.iter().chain(&right) {
+        child.borrow_mut().parent = Rc::downgrade(&node);
+    }
+    let mut node_ref = node.borrow_mut();
+    node_ref.left = left;
+    node_ref.right = right;
+    drop(node_ref);
+    Some(node)
 }
 
 fn min_from<T: Ord>(start: NodePointer<T>, stats: &mut Stats) -> NodePointer<T> {
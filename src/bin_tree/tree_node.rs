@@ -1,22 +1,24 @@
 use std::cell::RefCell;
-use std::fmt::{Debug, Formatter};
+use std::cmp::Ordering;
+use std::fmt::Debug;
 use std::rc::{Rc, Weak};
-use itertools::Itertools;
+use crate::dot::DotNode;
+use crate::live::Live;
+use crate::shape::NodeView;
+use crate::structure::StructureKind;
+use crate::validate::CheckedNode;
 
 
 pub(super) type NodePointer<T> = Rc<RefCell<Node<T>>>;
 pub(super) type NodeWeak<T> = Weak<RefCell<Node<T>>>;
 
-pub enum Direction {
-    Left,
-    Right,
-}
-
 pub struct Node<T: Ord> {
     pub(super) parent: NodeWeak<T>,
     pub(super) val: T,
     pub(super) left: Option<NodePointer<T>>,
     pub(super) right: Option<NodePointer<T>>,
+    // held for its drop
+    _live: Live,
 }
 
 impl<T: Ord> Node<T> {
@@ -26,6 +28,7 @@ impl<T: Ord> Node<T> {
             val,
             left: None,
             right: None,
+            _live: Live::new(StructureKind::BinTree),
         }
     }
 
@@ -33,10 +36,6 @@ impl<T: Ord> Node<T> {
         Rc::new(RefCell::new(Node::new(val, parent)))
     }
 
-    pub fn val(&self) -> &T {
-        &self.val
-    }
-
     pub(super) fn measure_height(&self) -> usize {
         let left_height = match &self.left {
             Some(left) => left.borrow().measure_height(),
@@ -50,32 +49,32 @@ impl<T: Ord> Node<T> {
     }
 }
 
-impl<T: Ord + Debug> Node<T> {
-    pub(super) fn print_node(&self, f: &mut Formatter, road: String) {
-        if let Some(right) = &self.right {
-            right.borrow().print_node(f, road.clone() + "u");
-        }
+impl<T: Ord> NodeView for NodePointer<T> {
+    fn left(&self) -> Option<Self> {
+        self.borrow().left.clone()
+    }
 
-        write!(f, "   ").unwrap();
-        for (x, y) in road.chars().tuple_windows() {
-            if x != y {
-                write!(f, "│  ").unwrap();
-            } else {
-                write!(f, "   ").unwrap();
-            }
-        }
-        if let Some(last) = road.chars().last() {
-            if last == 'u' {
-                write!(f, "╭──").unwrap();
-            } else {
-                write!(f, "╰──").unwrap();
-            }
-        }
-        writeln!(f, "{:?}", &self.val).unwrap();
+    fn right(&self) -> Option<Self> {
+        self.borrow().right.clone()
+    }
 
-        if let Some(left) = &self.left {
-            left.borrow().print_node(f, road + "d");
-        }
+    fn id(&self) -> usize {
+        Rc::as_ptr(self) as usize
+    }
+
+    fn parent(&self) -> Option<Option<Self>> {
+        Some(self.borrow().parent.upgrade())
+    }
+}
+
+impl<T: Ord + Debug> DotNode for NodePointer<T> {
+    fn key(&self) -> String {
+        format!("{:?}", self.borrow().val)
     }
 }
 
+impl<T: Ord> CheckedNode for NodePointer<T> {
+    fn cmp_key(&self, other: &Self) -> Ordering {
+        self.borrow().val.cmp(&other.borrow().val)
+    }
+}
@@ -0,0 +1,523 @@
+use std::fmt::{Display, Formatter};
+use std::io::IsTerminal;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use plotters::chart::ChartBuilder;
+use plotters::coord::ranged1d::{AsRangedCoord, Ranged, ValueFormatter};
+use plotters::coord::Shift;
+use plotters::prelude::{BitMapBackend, Color, DrawingArea, DrawingBackend, IntoDrawingArea, IntoFont, IntoLogRange, Palette, Polygon, Rectangle, SVGBackend, SeriesLabelPosition, WHITE};
+use plotters::series::LineSeries;
+use plotters::style::{BLACK, Palette99};
+use serde::{Deserialize, Serialize};
+
+mod terminal;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Format {
+    #[default]
+    Png,
+    Svg,
+    // braille line charts printed to stdout, for machines without an image viewer
+    Terminal,
+}
+
+// per-operation values divided by a growth function of n, flat lines mean that growth rate
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Normalize {
+    Log2N,
+    LnN,
+    N,
+}
+
+impl Normalize {
+    pub const ALL: [Normalize; 3] = [Normalize::Log2N, Normalize::LnN, Normalize::N];
+
+    pub fn key(&self) -> &'static str {
+        match self {
+            Normalize::Log2N => "log2_n",
+            Normalize::LnN => "ln_n",
+            Normalize::N => "n",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Normalize::Log2N => "log2 n",
+            Normalize::LnN => "ln n",
+            Normalize::N => "n",
+        }
+    }
+
+    pub fn apply(&self, n: f64, y: f64) -> f64 {
+        let divisor = match self {
+            Normalize::Log2N => n.log2(),
+            Normalize::LnN => n.ln(),
+            Normalize::N => n,
+        };
+        y / divisor
+    }
+}
+
+impl FromStr for Normalize {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Normalize::ALL.into_iter()
+            .find(|normalize| normalize.key() == s)
+            .ok_or_else(|| format!("unknown normalization `{}`", s))
+    }
+}
+
+// shaded area around a line, one low and one high value per n
+#[derive(Clone, Debug, PartialEq)]
+pub struct Band {
+    pub low: Vec<f64>,
+    pub high: Vec<f64>,
+}
+
+impl Band {
+    // mean of every sample set with a band one standard deviation either side of it
+    pub fn stddev(samples: &[Vec<f64>]) -> (Vec<f64>, Band) {
+        let mut means = Vec::new();
+        let mut band = Band { low: Vec::new(), high: Vec::new() };
+        for values in samples {
+            let count = values.len() as f64;
+            let mean = values.iter().sum::<f64>() / count;
+            let variance = values.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (count - 1.0).max(1.0);
+            means.push(mean);
+            band.low.push(mean - variance.sqrt());
+            band.high.push(mean + variance.sqrt());
+        }
+        (means, band)
+    }
+}
+
+pub struct Series<'a> {
+    pub name: &'a str,
+    pub values: Vec<f64>,
+    pub band: Option<Band>,
+}
+
+// where and how charts are drawn; the name passed to draw_chart fills in the file name and title
+// unless they are set here
+#[derive(Clone, Debug)]
+pub struct ChartOptions {
+    dir: PathBuf,
+    file_name: Option<String>,
+    format: Format,
+    size: (u32, u32),
+    font: String,
+    title: Option<String>,
+    x_label: Option<String>,
+    y_label: Option<String>,
+    y_from_zero: bool,
+    log_x: bool,
+    log_y: bool,
+    normalize: Option<Normalize>,
+    bands: bool,
+    // columns and rows of a terminal chart
+    text_size: (usize, usize),
+}
+
+#[derive(Debug)]
+pub enum ChartError {
+    Empty(String),
+    Io(PathBuf, std::io::Error),
+    Draw(PathBuf, String),
+}
+
+impl Default for ChartOptions {
+    fn default() -> Self {
+        ChartOptions {
+            dir: PathBuf::from("charts"),
+            file_name: None,
+            format: Format::Png,
+            size: (1280, 720),
+            font: "Calibri".to_string(),
+            title: None,
+            x_label: None,
+            y_label: None,
+            y_from_zero: true,
+            log_x: false,
+            log_y: false,
+            normalize: None,
+            bands: false,
+            text_size: (80, 20),
+        }
+    }
+}
+
+impl ChartOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.dir = dir.into();
+        self
+    }
+
+    // without extension, it follows the format
+    pub fn file_name(mut self, file_name: impl Into<String>) -> Self {
+        self.file_name = Some(file_name.into());
+        self
+    }
+
+    pub fn format(mut self, format: Format) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn size(mut self, width: u32, height: u32) -> Self {
+        self.size = (width, height);
+        self
+    }
+
+    pub fn font(mut self, font: impl Into<String>) -> Self {
+        self.font = font.into();
+        self
+    }
+
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    pub fn x_label(mut self, label: impl Into<String>) -> Self {
+        self.x_label = Some(label.into());
+        self
+    }
+
+    pub fn y_label(mut self, label: impl Into<String>) -> Self {
+        self.y_label = Some(label.into());
+        self
+    }
+
+    // false fits the y axis to the data instead of starting it at 0
+    pub fn y_from_zero(mut self, y_from_zero: bool) -> Self {
+        self.y_from_zero = y_from_zero;
+        self
+    }
+
+    pub fn log_x(mut self, log_x: bool) -> Self {
+        self.log_x = log_x;
+        self
+    }
+
+    // points at or below zero can't be shown and are left out
+    pub fn log_y(mut self, log_y: bool) -> Self {
+        self.log_y = log_y;
+        self
+    }
+
+    pub fn normalize(mut self, normalize: Option<Normalize>) -> Self {
+        self.normalize = normalize;
+        self
+    }
+
+    // asks the callers that have them for min/max or stddev bands, draw_series takes them either way
+    pub fn bands(mut self, bands: bool) -> Self {
+        self.bands = bands;
+        self
+    }
+
+    pub fn text_size(mut self, columns: usize, rows: usize) -> Self {
+        self.text_size = (columns, rows);
+        self
+    }
+
+    pub fn wants_bands(&self) -> bool {
+        self.bands
+    }
+
+    pub fn out_dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn out_format(&self) -> Format {
+        self.format
+    }
+
+    pub fn out_size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    pub fn font_name(&self) -> &str {
+        &self.font
+    }
+
+    // the y label with the normalization it was divided by
+    fn y_label_text(&self) -> Option<String> {
+        match (&self.y_label, self.normalize) {
+            (Some(label), Some(normalize)) => Some(format!("{} / {}", label, normalize.label())),
+            (Some(label), None) => Some(label.clone()),
+            (None, Some(normalize)) => Some(format!("value / {}", normalize.label())),
+            (None, None) => None,
+        }
+    }
+
+    pub fn path(&self, name: &str) -> PathBuf {
+        let extension = match self.format {
+            Format::Png => "png",
+            Format::Svg => "svg",
+            Format::Terminal => "txt",
+        };
+        let file_name = self.file_name.clone().unwrap_or_else(|| format!("chart_{}", name));
+        self.dir.join(format!("{}.{}", file_name, extension))
+    }
+}
+
+impl Display for ChartError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            ChartError::Empty(name) => write!(f, "nothing to draw in chart {}", name),
+            ChartError::Io(path, e) => write!(f, "cannot create {}: {}", path.display(), e),
+            ChartError::Draw(path, e) => write!(f, "cannot draw {}: {}", path.display(), e),
+        }
+    }
+}
+
+impl std::error::Error for ChartError {}
+
+// one line per data series over n_range, returns the file written, None when printed to the terminal
+pub fn draw_chart<T>(
+    data: Vec<Vec<T>>,
+    names: Vec<&str>,
+    n_range: impl Iterator<Item=usize> + Clone,
+    name: &str,
+    options: &ChartOptions,
+    scale: impl Fn(f64, f64) -> f64,
+) -> Result<Option<PathBuf>, ChartError>
+    where T: Clone + PartialOrd, f64: From<T> {
+    let series = data.into_iter().zip(names)
+        .map(|(vals, name)| Series {
+            name,
+            values: n_range.clone().zip(vals).map(|(x, y)| scale(x as f64, f64::from(y))).collect(),
+            band: None,
+        })
+        .collect();
+    draw_series(series, n_range, name, options)
+}
+
+// like draw_chart, with an optional band drawn under each line
+pub fn draw_series(
+    series: Vec<Series>,
+    n_range: impl Iterator<Item=usize> + Clone,
+    name: &str,
+    options: &ChartOptions,
+) -> Result<Option<PathBuf>, ChartError> {
+    let file = options.path(name);
+    let plotted = Plotted::new(&series, n_range, options.title.as_deref().unwrap_or(name), options)
+        .ok_or_else(|| ChartError::Empty(name.to_string()))?;
+    if options.format == Format::Terminal {
+        print!("{}", terminal::render(&plotted, options, stdout_color()));
+        return Ok(None);
+    }
+
+    std::fs::create_dir_all(&options.dir).map_err(|e| ChartError::Io(file.clone(), e))?;
+    match options.format {
+        Format::Png => draw_single(BitMapBackend::new(&file, options.size).into_drawing_area(), plotted, options),
+        Format::Svg => draw_single(SVGBackend::new(&file, options.size).into_drawing_area(), plotted, options),
+        Format::Terminal => unreachable!(),
+    }.map_err(|e| ChartError::Draw(file.clone(), e))?;
+    Ok(Some(file))
+}
+
+// the text draw_series prints for Format::Terminal, whatever the format of the options
+pub fn render_text(
+    series: Vec<Series>,
+    n_range: impl Iterator<Item=usize> + Clone,
+    name: &str,
+    options: &ChartOptions,
+    color: bool,
+) -> Result<String, ChartError> {
+    let plotted = Plotted::new(&series, n_range, options.title.as_deref().unwrap_or(name), options)
+        .ok_or_else(|| ChartError::Empty(name.to_string()))?;
+    Ok(terminal::render(&plotted, options, color))
+}
+
+// ansi colors only when stdout is a terminal and NO_COLOR (https://no-color.org) is unset,
+// piped or redirected output gets none
+pub fn stdout_color() -> bool {
+    std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none()
+}
+
+// one small chart of a grid, with its own title and y axis
+pub struct Panel<'a> {
+    pub title: String,
+    pub y_label: String,
+    pub series: Vec<Series<'a>>,
+}
+
+// all panels in one image, options.size is the size of a single panel; on the terminal they come one after another
+pub fn draw_grid(
+    panels: Vec<Panel>,
+    n_range: impl Iterator<Item=usize> + Clone,
+    name: &str,
+    options: &ChartOptions,
+) -> Result<Option<PathBuf>, ChartError> {
+    let file = options.path(name);
+    let plotted = panels.iter()
+        .map(|panel| {
+            let options = options.clone().y_label(&panel.y_label);
+            Plotted::new(&panel.series, n_range.clone(), &panel.title, &options).map(|plotted| (plotted, options))
+        })
+        .collect::<Vec<_>>();
+    if plotted.iter().all(Option::is_none) {
+        return Err(ChartError::Empty(name.to_string()));
+    }
+    if options.format == Format::Terminal {
+        for (plotted, options) in plotted.iter().flatten() {
+            print!("{}", terminal::render(plotted, options, stdout_color()));
+        }
+        return Ok(None);
+    }
+
+    // as square as it gets, filled row by row
+    let columns = (plotted.len() as f64).sqrt().ceil() as usize;
+    let rows = plotted.len().div_ceil(columns);
+    let size = (options.size.0 * columns as u32, options.size.1 * rows as u32);
+    let title = options.title.as_deref().unwrap_or(name);
+
+    std::fs::create_dir_all(&options.dir).map_err(|e| ChartError::Io(file.clone(), e))?;
+    match options.format {
+        Format::Png => draw_panels(BitMapBackend::new(&file, size).into_drawing_area(), plotted, (rows, columns), title, options),
+        Format::Svg => draw_panels(SVGBackend::new(&file, size).into_drawing_area(), plotted, (rows, columns), title, options),
+        Format::Terminal => unreachable!(),
+    }.map_err(|e| ChartError::Draw(file.clone(), e))?;
+    Ok(Some(file))
+}
+
+fn draw_single<DB: DrawingBackend>(area: DrawingArea<DB, Shift>, plotted: Plotted, options: &ChartOptions) -> Result<(), String> {
+    area.fill(&WHITE).map_err(|e| e.to_string())?;
+    plot_axes(&area, plotted, options)?;
+    area.present().map_err(|e| e.to_string())
+}
+
+fn draw_panels<DB: DrawingBackend>(
+    area: DrawingArea<DB, Shift>,
+    panels: Vec<Option<(Plotted, ChartOptions)>>,
+    grid: (usize, usize),
+    title: &str,
+    options: &ChartOptions,
+) -> Result<(), String> {
+    area.fill(&WHITE).map_err(|e| e.to_string())?;
+    let inner = area.titled(title, (options.font.as_str(), 50)).map_err(|e| e.to_string())?;
+    // panels with nothing to show stay blank
+    for (cell, panel) in inner.split_evenly(grid).iter().zip(panels) {
+        if let Some((plotted, options)) = panel {
+            plot_axes(cell, plotted, &options)?;
+        }
+    }
+    area.present().map_err(|e| e.to_string())
+}
+
+struct Plotted<'a> {
+    lines: Vec<Vec<(f64, f64)>>,
+    bands: Vec<Option<Vec<(f64, f64)>>>,
+    names: Vec<&'a str>,
+    title: &'a str,
+    x_range: Range<f64>,
+    y_range: Range<f64>,
+}
+
+impl<'a> Plotted<'a> {
+    // the points that can be shown and the axis ranges around them, None when no point is left
+    fn new(series: &[Series<'a>], n_range: impl Iterator<Item=usize> + Clone, title: &'a str, options: &ChartOptions) -> Option<Self> {
+        let (first, last) = (n_range.clone().next()?, n_range.clone().last()?);
+
+        let shown = |y: f64| y.is_finite() && (!options.log_y || y > 0.0);
+        let normalize = |x: f64, y: f64| options.normalize.map_or(y, |normalize| normalize.apply(x, y));
+        let points = |values: &[f64]| n_range.clone().zip(values)
+            .map(|(x, &y)| (x as f64, normalize(x as f64, y)))
+            .filter(|&(_, y)| shown(y))
+            .collect::<Vec<_>>();
+
+        let lines = series.iter().map(|s| points(&s.values)).collect::<Vec<_>>();
+        let bands = series.iter()
+            .map(|s| s.band.as_ref().map(|band| {
+                // low side left to right, then the high side back, as one closed outline
+                let mut outline = points(&band.low);
+                outline.extend(points(&band.high).into_iter().rev());
+                outline
+            }))
+            .collect::<Vec<_>>();
+
+        let all = lines.iter().flatten().chain(bands.iter().flatten().flatten());
+        all.clone().next()?;
+        let max = all.clone().map(|&(_, y)| y).fold(f64::MIN, f64::max);
+        let min = all.map(|&(_, y)| y).fold(f64::MAX, f64::min);
+
+        let x_range = first as f64..(last as f64).max(first as f64 + 1.0);
+        let y_range = if options.log_y {
+            min / 1.2..max * 1.2
+        } else {
+            let (low, high) = if options.y_from_zero { (min.min(0.0), max.max(0.0)) } else { (min, max) };
+            // a flat line still gets some room above and below it
+            let pad = if high > low { (high - low) * 0.1 } else { high.abs().max(1.0) * 0.1 };
+            if options.y_from_zero && low == 0.0 { 0.0..high + pad } else { low - pad..high + pad }
+        };
+
+        let names = series.iter().map(|s| s.name).collect();
+        Some(Plotted { lines, bands, names, title, x_range, y_range })
+    }
+}
+
+// every combination of linear and log axes is its own coordinate type
+fn plot_axes<DB: DrawingBackend>(area: &DrawingArea<DB, Shift>, plotted: Plotted, options: &ChartOptions) -> Result<(), String> {
+    let (x_range, y_range) = (plotted.x_range.clone(), plotted.y_range.clone());
+    match (options.log_x, options.log_y) {
+        (false, false) => plot(area, plotted, x_range, y_range, options),
+        (true, false) => plot(area, plotted, x_range.log_scale(), y_range, options),
+        (false, true) => plot(area, plotted, x_range, y_range.log_scale(), options),
+        (true, true) => plot(area, plotted, x_range.log_scale(), y_range.log_scale(), options),
+    }
+}
+
+fn plot<DB, X, Y>(area: &DrawingArea<DB, Shift>, plotted: Plotted, x_spec: X, y_spec: Y, options: &ChartOptions) -> Result<(), String>
+where
+    DB: DrawingBackend,
+    X: AsRangedCoord<Value=f64>,
+    Y: AsRangedCoord<Value=f64>,
+    X::CoordDescType: Ranged<ValueType=f64> + ValueFormatter<f64>,
+    Y::CoordDescType: Ranged<ValueType=f64> + ValueFormatter<f64>,
+{
+    let font = options.font.as_str();
+    let mut ctx = ChartBuilder::on(area)
+        .margin(5)
+        .caption(plotted.title, (font, 40).into_font())
+        // axis descriptions need room next to the tick labels
+        .set_all_label_area_size(if options.x_label.is_some() || options.y_label.is_some() { 60 } else { 40 })
+        .build_cartesian_2d(x_spec, y_spec)
+        .map_err(|e| e.to_string())?;
+
+    let mut mesh = ctx.configure_mesh();
+    if let Some(label) = &options.x_label {
+        mesh.x_desc(label);
+    }
+    if let Some(label) = options.y_label_text() {
+        mesh.y_desc(label);
+    }
+    mesh.axis_desc_style((font, 20)).draw().map_err(|e| e.to_string())?;
+
+    // bands first so every line stays visible on top of them
+    for (num, band) in plotted.bands.into_iter().enumerate() {
+        if let Some(outline) = band.filter(|outline| outline.len() > 2) {
+            ctx.draw_series(std::iter::once(Polygon::new(outline, Palette99::pick(num).mix(0.2).filled())))
+                .map_err(|e| e.to_string())?;
+        }
+    }
+
+    for (num, (vals, name)) in plotted.lines.into_iter().zip(plotted.names).enumerate() {
+        ctx.draw_series(LineSeries::new(vals, Palette99::pick(num)))
+            .map_err(|e| e.to_string())?
+            .label(name).legend(move |(x, y)| Rectangle::new([(x, y - 8), (x + 15, y + 7)], Palette99::pick(num).filled()));
+    }
+
+    ctx.configure_series_labels().border_style(BLACK).label_font((font, 20)).position(SeriesLabelPosition::UpperLeft).background_style(WHITE).draw()
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,161 @@
+use std::fmt::Write as _;
+
+use super::{ChartOptions, Plotted};
+
+// ansi colors in the order plotters' Palette99 starts with
+const COLORS: [u8; 6] = [31, 32, 33, 34, 35, 36];
+
+// drawn over the braille at each point of a series and in front of its legend entry, so the
+// series stay apart without color and when there are more of them than colors
+const MARKERS: [char; 12] = ['●', '▲', '■', '◆', '○', '△', '□', '◇', 'x', '+', '*', '#'];
+
+// each character is a 2 x 4 grid of braille dots, numbered the way the unicode block encodes them
+const DOTS: [[u32; 4]; 2] = [[0x01, 0x02, 0x04, 0x40], [0x08, 0x10, 0x20, 0x80]];
+
+struct Canvas {
+    columns: usize,
+    rows: usize,
+    dots: Vec<u32>,
+    // the series that drew last in a cell gives it its color
+    colors: Vec<Option<usize>>,
+    // the series whose point falls in a cell, its marker replaces the braille there
+    marks: Vec<Option<usize>>,
+}
+
+impl Canvas {
+    fn new(columns: usize, rows: usize) -> Self {
+        Canvas { columns, rows, dots: vec![0; columns * rows], colors: vec![None; columns * rows], marks: vec![None; columns * rows] }
+    }
+
+    fn mark(&mut self, x: usize, y: usize, series: usize) {
+        self.marks[y / 4 * self.columns + x / 2] = Some(series);
+    }
+
+    fn set(&mut self, x: usize, y: usize, series: usize) {
+        let cell = y / 4 * self.columns + x / 2;
+        self.dots[cell] |= DOTS[x % 2][y % 4];
+        self.colors[cell] = Some(series);
+    }
+
+    // bresenham between two dots
+    fn line(&mut self, from: (usize, usize), to: (usize, usize), series: usize) {
+        let (mut x, mut y) = (from.0 as isize, from.1 as isize);
+        let (x1, y1) = (to.0 as isize, to.1 as isize);
+        let (dx, dy) = ((x1 - x).abs(), -(y1 - y).abs());
+        let (sx, sy) = ((x1 - x).signum(), (y1 - y).signum());
+        let mut err = dx + dy;
+        loop {
+            self.set(x as usize, y as usize, series);
+            if x == x1 && y == y1 {
+                break;
+            }
+            let e2 = 2 * err;
+            if e2 >= dy {
+                err += dy;
+                x += sx;
+            }
+            if e2 <= dx {
+                err += dx;
+                y += sy;
+            }
+        }
+    }
+
+    fn row(&self, row: usize, color: bool) -> String {
+        let mut out = String::new();
+        for cell in row * self.columns..(row + 1) * self.columns {
+            let (glyph, series) = match self.marks[cell] {
+                Some(series) => (marker(series), Some(series)),
+                None => (char::from_u32(0x2800 + self.dots[cell]).unwrap(), self.colors[cell]),
+            };
+            match series {
+                Some(series) if color => write!(out, "\x1b[{}m{}\x1b[0m", COLORS[series % COLORS.len()], glyph).unwrap(),
+                _ => out.push(glyph),
+            }
+        }
+        out
+    }
+}
+
+fn marker(series: usize) -> char {
+    MARKERS[series % MARKERS.len()]
+}
+
+fn tick(value: f64) -> String {
+    if value != 0.0 && (value.abs() >= 1e6 || value.abs() < 1e-2) {
+        format!("{:.2e}", value)
+    } else {
+        format!("{:.2}", value)
+    }
+}
+
+// braille line chart of the same points the image backends draw, bands are left out
+pub(super) fn render(plotted: &Plotted, options: &ChartOptions, color: bool) -> String {
+    let (columns, rows) = options.text_size;
+    let scale = |log: bool, v: f64| if log { v.log10() } else { v };
+    let (x0, x1) = (scale(options.log_x, plotted.x_range.start), scale(options.log_x, plotted.x_range.end));
+    let (y0, y1) = (scale(options.log_y, plotted.y_range.start), scale(options.log_y, plotted.y_range.end));
+    let unscale_y = |v: f64| if options.log_y { 10f64.powf(v) } else { v };
+
+    let labels = [y1, (y0 + y1) / 2.0, y0].map(|v| tick(unscale_y(v)));
+    let label_width = labels.iter().map(String::len).max().unwrap_or(0);
+    let plot_columns = columns.saturating_sub(label_width + 2).max(2);
+    let mut canvas = Canvas::new(plot_columns, rows.max(2));
+    let (width, height) = (plot_columns * 2, canvas.rows * 4);
+
+    let dot = |(x, y): (f64, f64)| {
+        let fx = ((scale(options.log_x, x) - x0) / (x1 - x0)).clamp(0.0, 1.0);
+        let fy = ((scale(options.log_y, y) - y0) / (y1 - y0)).clamp(0.0, 1.0);
+        ((fx * (width - 1) as f64).round() as usize, height - 1 - (fy * (height - 1) as f64).round() as usize)
+    };
+    let mut points = Vec::new();
+    for (series, line) in plotted.lines.iter().enumerate() {
+        let dots = line.iter().map(|&p| dot(p)).collect::<Vec<_>>();
+        for pair in dots.windows(2) {
+            canvas.line(pair[0], pair[1], series);
+        }
+        if let [only] = dots[..] {
+            canvas.set(only.0, only.1, series);
+        }
+        points.extend(dots.into_iter().map(|p| (p, series)));
+    }
+    // after all the lines, or a later line would run over the markers of an earlier one
+    for ((x, y), series) in points {
+        canvas.mark(x, y, series);
+    }
+
+    let mut out = String::new();
+    writeln!(out, "{:^width$}", plotted.title, width = columns).unwrap();
+    let y_label = options.y_label_text();
+    if let Some(label) = &y_label {
+        writeln!(out, "{}", label).unwrap();
+    }
+    let middle = canvas.rows / 2;
+    for row in 0..canvas.rows {
+        let label = match row {
+            0 => labels[0].as_str(),
+            r if r == middle => labels[1].as_str(),
+            r if r == canvas.rows - 1 => labels[2].as_str(),
+            _ => "",
+        };
+        let axis = if label.is_empty() { '│' } else { '┤' };
+        writeln!(out, "{:>w$} {}{}", label, axis, canvas.row(row, color), w = label_width).unwrap();
+    }
+    writeln!(out, "{:>w$} └{}", "", "─".repeat(plot_columns), w = label_width).unwrap();
+
+    let (first, last) = (tick(plotted.x_range.start), tick(plotted.x_range.end));
+    let gap = plot_columns.saturating_sub(first.len() + last.len()).max(1);
+    writeln!(out, "{:>w$}  {}{}{}", "", first, " ".repeat(gap), last, w = label_width).unwrap();
+    if let Some(label) = &options.x_label {
+        writeln!(out, "{:>w$}", label, w = label_width + 2 + plot_columns).unwrap();
+    }
+
+    let legend = plotted.names.iter().enumerate()
+        .map(|(series, name)| match color {
+            true => format!("\x1b[{}m{}\x1b[0m {}", COLORS[series % COLORS.len()], marker(series), name),
+            false => format!("{} {}", marker(series), name),
+        })
+        .collect::<Vec<_>>();
+    writeln!(out, "{:>w$}  {}", "", legend.join("  "), w = label_width).unwrap();
+    out
+}
@@ -0,0 +1,140 @@
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use crate::config::Config;
+use crate::experiment::Data;
+use crate::results::{data_columns, parse_data_columns};
+use crate::structure::StructureKind;
+
+// append-only csv of finished (structure, n, rep) cells, so an interrupted sweep can pick up where it stopped
+//
+// # name: random
+// # workload: random
+// # seed: 42
+// # measure: height
+// structure,n,rep,phase,count,...,phase_nanos
+pub struct Checkpoint {
+    file: File,
+    seed: u64,
+    done: HashMap<(StructureKind, usize, usize), (Data, Data)>,
+}
+
+impl Checkpoint {
+    // reads the cells already in the file, or starts a new one; the seed of an existing file wins
+    // over a missing config seed, and a file from a different experiment is refused
+    pub fn open(path: &Path, config: &Config) -> io::Result<Self> {
+        let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path.display(), msg));
+        let header = |seed: u64| {
+            format!("# name: {}\n# workload: {}\n# seed: {}\n# measure: {}\nstructure,n,rep,phase,{},phase_nanos\n",
+                    config.name, config.workload, seed, measure_name(config), Data::FIELDS.join(","))
+        };
+
+        if !path.exists() || std::fs::metadata(path)?.len() == 0 {
+            let seed = config.seed.unwrap_or_else(rand::random);
+            let mut file = File::create(path)?;
+            file.write_all(header(seed).as_bytes())?;
+            file.flush()?;
+            return Ok(Checkpoint { file, seed, done: HashMap::new() });
+        }
+
+        let mut seed = None;
+        let mut expected = vec![("name", config.name.clone()), ("workload", config.workload.to_string()), ("measure", measure_name(config))];
+        if let Some(config_seed) = config.seed {
+            expected.push(("seed", config_seed.to_string()));
+        }
+
+        // insert rows wait here for the delete row of the same cell
+        let mut pending = HashMap::new();
+        let mut done = HashMap::new();
+        // rows are written whole with their newline, anything after the last one is a cut-off write
+        let text = std::fs::read_to_string(path)?;
+        let valid = text.rfind('\n').map_or(0, |i| i + 1);
+        for (i, line) in text[..valid].lines().enumerate() {
+            if let Some(comment) = line.strip_prefix('#') {
+                let Some((key, value)) = comment.split_once(':') else { continue };
+                let (key, value) = (key.trim(), value.trim());
+                if let Some((_, want)) = expected.iter().find(|(k, _)| *k == key) {
+                    if want != value {
+                        return Err(invalid(format!("checkpoint has {} `{}`, the experiment has `{}`", key, value, want)));
+                    }
+                }
+                if key == "seed" {
+                    seed = Some(value.parse::<u64>().map_err(|e| invalid(e.to_string()))?);
+                }
+                continue;
+            }
+            if line.trim().is_empty() || line.starts_with("structure,") {
+                continue;
+            }
+
+            let cols = line.split(',').collect::<Vec<_>>();
+            let parse = || -> Result<_, String> {
+                // the whole row is counted here, parse_data_columns would only count the part after the phase
+                if cols.len() != 4 + Data::FIELDS.len() + 1 {
+                    return Err(format!("expected {} columns, got {}", 4 + Data::FIELDS.len() + 1, cols.len()));
+                }
+                let structure = cols[0].parse::<StructureKind>()?;
+                let n = cols[1].parse::<usize>().map_err(|e| e.to_string())?;
+                let rep = cols[2].parse::<usize>().map_err(|e| e.to_string())?;
+                let data = parse_data_columns(&cols[4..])?;
+                Ok(((structure, n, rep), data))
+            };
+            let (cell, data) = parse().map_err(|e| invalid(format!("line {}: {}", i + 1, e)))?;
+            match cols[3] {
+                "insert" => {
+                    pending.insert(cell, data);
+                }
+                "delete" => {
+                    // an insert row without its delete row means the cell was cut off, it runs again
+                    if let Some(insert) = pending.remove(&cell) {
+                        done.insert(cell, (insert, data));
+                    }
+                }
+                phase => return Err(invalid(format!("line {}: unknown phase `{}`", i + 1, phase))),
+            }
+        }
+
+        let seed = seed.ok_or_else(|| invalid("missing `# seed:` line".to_string()))?;
+        let mut file = OpenOptions::new().write(true).open(path)?;
+        file.set_len(valid as u64)?;
+        file.seek(SeekFrom::End(0))?;
+        Ok(Checkpoint { file, seed, done })
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn completed(&self) -> usize {
+        self.done.len()
+    }
+
+    pub fn contains(&self, structure: StructureKind, n: usize, rep: usize) -> bool {
+        self.done.contains_key(&(structure, n, rep))
+    }
+
+    pub fn take(&mut self, structure: StructureKind, n: usize, rep: usize) -> Option<(Data, Data)> {
+        self.done.remove(&(structure, n, rep))
+    }
+
+    // written and flushed right away, both phases of a cell end up on disk together
+    pub fn record(&mut self, structure: StructureKind, n: usize, rep: usize, insert: &Data, delete: &Data) -> io::Result<()> {
+        let rows = format!("{},{},{},insert,{}\n{},{},{},delete,{}\n",
+                           structure, n, rep, data_columns(insert),
+                           structure, n, rep, data_columns(delete));
+        self.file.write_all(rows.as_bytes())?;
+        self.file.flush()
+    }
+}
+
+fn measure_name(config: &Config) -> String {
+    let measure = config.measure();
+    match (measure.height, measure.timing) {
+        (false, false) => "counts",
+        (true, false) => "height",
+        (false, true) => "timing",
+        (true, true) => "height timing",
+    }.to_string()
+}
@@ -0,0 +1,161 @@
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::iter::StepBy;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::chart::{Format, Normalize};
+use crate::experiment::Metric;
+use crate::runner::Measure;
+use crate::structure::StructureKind;
+use crate::workload::Workload;
+
+// experiment description, read from a .toml or .json file
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    pub name: String,
+    pub structures: Vec<StructureKind>,
+    pub sizes: SizeSweep,
+    pub reps: usize,
+    // extra repetitions run first and thrown away, to warm caches and the allocator for timing
+    #[serde(default)]
+    pub warmup: usize,
+    // jobs running at once, 0 for one per core; timings are only trustworthy with 1
+    #[serde(default = "one_thread")]
+    pub threads: usize,
+    pub workload: Workload,
+    // drawn at random when missing, the value used ends up in the outputs
+    #[serde(default)]
+    pub seed: Option<u64>,
+    #[serde(default = "all_metrics")]
+    pub metrics: Vec<Metric>,
+    #[serde(default)]
+    pub outputs: Outputs,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SizeSweep {
+    pub start: usize,
+    pub end: usize,
+    pub step: usize,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Outputs {
+    // results files and charts both go here
+    pub dir: PathBuf,
+    pub format: Format,
+    pub log_x: bool,
+    pub log_y: bool,
+    // divide every value by log2 n, ln n or n before drawing
+    pub normalize: Option<Normalize>,
+    // shaded avg..max and ±stddev areas around the lines
+    pub bands: bool,
+    pub charts: bool,
+    // per-metric charts with one line per structure
+    pub comparison: bool,
+    // grids of all metrics and a <name>_index.md with summary tables
+    pub report: bool,
+    pub csv: bool,
+    pub json: bool,
+    // finished cells are kept here, rerunning the same experiment resumes from it
+    pub checkpoint: Option<PathBuf>,
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(PathBuf, std::io::Error),
+    Parse(PathBuf, String),
+    Invalid(String),
+}
+
+fn one_thread() -> usize {
+    1
+}
+
+fn all_metrics() -> Vec<Metric> {
+    Metric::COUNTERS.to_vec()
+}
+
+impl Default for Outputs {
+    fn default() -> Self {
+        Outputs {
+            dir: PathBuf::from("charts"),
+            format: Format::Png,
+            log_x: false,
+            log_y: false,
+            normalize: None,
+            bands: false,
+            charts: true,
+            comparison: false,
+            report: false,
+            csv: false,
+            json: false,
+            checkpoint: None,
+        }
+    }
+}
+
+impl SizeSweep {
+    pub fn iter(&self) -> StepBy<RangeInclusive<usize>> {
+        (self.start..=self.end).step_by(self.step)
+    }
+}
+
+impl Config {
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        let text = fs::read_to_string(path).map_err(|e| ConfigError::Io(path.to_path_buf(), e))?;
+
+        let config: Config = match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => serde_json::from_str(&text)
+                .map_err(|e| ConfigError::Parse(path.to_path_buf(), e.to_string()))?,
+            _ => toml::from_str(&text)
+                .map_err(|e| ConfigError::Parse(path.to_path_buf(), e.to_string()))?,
+        };
+
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.structures.is_empty() {
+            return Err(ConfigError::Invalid("no structures listed".to_string()));
+        }
+        if self.metrics.is_empty() {
+            return Err(ConfigError::Invalid("no metrics listed".to_string()));
+        }
+        if self.reps == 0 {
+            return Err(ConfigError::Invalid("reps must be at least 1".to_string()));
+        }
+        if self.sizes.step == 0 || self.sizes.start == 0 || self.sizes.start > self.sizes.end {
+            return Err(ConfigError::Invalid(format!(
+                "bad size sweep {}..={} step {}", self.sizes.start, self.sizes.end, self.sizes.step
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn measure(&self) -> Measure {
+        Measure {
+            height: self.metrics.iter().any(|m| m.needs_height()),
+            timing: self.metrics.iter().any(|m| m.needs_timing()),
+        }
+    }
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            ConfigError::Io(path, e) => write!(f, "cannot read {}: {}", path.display(), e),
+            ConfigError::Parse(path, e) => write!(f, "cannot parse {}: {}", path.display(), e),
+            ConfigError::Invalid(e) => write!(f, "invalid config: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
@@ -0,0 +1,320 @@
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64;
+
+use crate::experiment::Stats;
+use crate::{bin_tree, rb_tree, splay_tree};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Op {
+    Insert(usize),
+    Delete(usize),
+    Contains(usize),
+    Iterate,
+}
+
+impl Op {
+    fn key(&self) -> Option<usize> {
+        match *self {
+            Op::Insert(key) | Op::Delete(key) | Op::Contains(key) => Some(key),
+            Op::Iterate => None,
+        }
+    }
+
+    fn with_key(&self, key: usize) -> Op {
+        match self {
+            Op::Insert(_) => Op::Insert(key),
+            Op::Delete(_) => Op::Delete(key),
+            Op::Contains(_) => Op::Contains(key),
+            Op::Iterate => Op::Iterate,
+        }
+    }
+}
+
+impl Display for Op {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            Op::Insert(key) => write!(f, "insert {}", key),
+            Op::Delete(key) => write!(f, "delete {}", key),
+            Op::Contains(key) => write!(f, "contains {}", key),
+            Op::Iterate => write!(f, "iterate"),
+        }
+    }
+}
+
+// keys from 0..keys, few enough that deletes and lookups hit and duplicates pile up
+pub fn random_ops(seed: u64, len: usize, keys: usize) -> Vec<Op> {
+    let rng = &mut Pcg64::seed_from_u64(seed);
+    (0..len).map(|_| {
+        let key = rng.random_range(0..keys);
+        match rng.random_range(0..100) {
+            0..40 => Op::Insert(key),
+            40..75 => Op::Delete(key),
+            75..95 => Op::Contains(key),
+            _ => Op::Iterate,
+        }
+    }).collect()
+}
+
+// two bytes an op, what kind it is and the key, so a fuzzer's mutations map onto small changes;
+// the keys stay below 256 and the kinds are weighted the way random_ops weighs them
+pub fn decode(data: &[u8]) -> Vec<Op> {
+    data.chunks_exact(2).map(|pair| {
+        let key = pair[1] as usize;
+        match pair[0] % 20 {
+            0..8 => Op::Insert(key),
+            8..15 => Op::Delete(key),
+            15..19 => Op::Contains(key),
+            _ => Op::Iterate,
+        }
+    }).collect()
+}
+
+// body of the fuzz targets; no shrinking here, the fuzzer aborts on the first panic
+// and minimizes the input itself
+pub fn fuzz_one<S: Subject>(data: &[u8]) {
+    if let Err(mismatch) = run_ops::<S>(&decode(data)) {
+        panic!("{} failed at step {} ({}): {}", S::NAME, mismatch.step, mismatch.op, mismatch.message);
+    }
+}
+
+// a tree as the harness drives it; the trees keep duplicates, so the model is a multiset
+pub trait Subject: Default {
+    const NAME: &'static str;
+
+    fn insert(&mut self, key: usize);
+
+    // false when the key wasn't there
+    fn delete(&mut self, key: usize) -> bool;
+
+    fn contains(&self, key: usize) -> bool;
+
+    // in order, duplicates repeated
+    fn keys(&self) -> Vec<usize>;
+
+    fn validate(&self) -> Result<(), String>;
+}
+
+impl Subject for bin_tree::BinTree<usize> {
+    const NAME: &'static str = "bin_tree";
+
+    fn insert(&mut self, key: usize) {
+        bin_tree::BinTree::insert(self, key, &mut Stats::new());
+    }
+
+    fn delete(&mut self, key: usize) -> bool {
+        bin_tree::BinTree::delete(self, key, &mut Stats::new())
+    }
+
+    fn contains(&self, key: usize) -> bool {
+        bin_tree::BinTree::contains(self, &key)
+    }
+
+    fn keys(&self) -> Vec<usize> {
+        self.iter().collect()
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        bin_tree::BinTree::validate(self).map_err(|e| e.to_string())
+    }
+}
+
+impl Subject for rb_tree::BinTree<usize> {
+    const NAME: &'static str = "rb_tree";
+
+    fn insert(&mut self, key: usize) {
+        rb_tree::BinTree::insert(self, key, &mut Stats::new());
+    }
+
+    fn delete(&mut self, key: usize) -> bool {
+        rb_tree::BinTree::delete(self, key, &mut Stats::new())
+    }
+
+    fn contains(&self, key: usize) -> bool {
+        rb_tree::BinTree::contains(self, &key)
+    }
+
+    fn keys(&self) -> Vec<usize> {
+        self.iter().collect()
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        rb_tree::BinTree::validate(self).map_err(|e| e.to_string())
+    }
+}
+
+impl Subject for splay_tree::SplayTree<usize> {
+    const NAME: &'static str = "splay_tree";
+
+    fn insert(&mut self, key: usize) {
+        splay_tree::SplayTree::insert(self, key, &mut Stats::new());
+    }
+
+    fn delete(&mut self, key: usize) -> bool {
+        splay_tree::SplayTree::delete(self, key, &mut Stats::new())
+    }
+
+    fn contains(&self, key: usize) -> bool {
+        splay_tree::SplayTree::contains(self, &key)
+    }
+
+    fn keys(&self) -> Vec<usize> {
+        self.iter().copied().collect()
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        splay_tree::SplayTree::validate(self).map_err(|e| e.to_string())
+    }
+}
+
+// the first step where the tree and the model disagree, or where the tree panicked
+#[derive(Clone, Debug, PartialEq)]
+pub struct Mismatch {
+    pub step: usize,
+    pub op: Op,
+    pub message: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Failure {
+    pub tree: &'static str,
+    // length of the sequence that failed first
+    pub original: usize,
+    // the shortest sequence with the smallest keys found that still fails
+    pub ops: Vec<Op>,
+    pub mismatch: Mismatch,
+}
+
+impl Display for Failure {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        writeln!(f, "{} failed, shrunk from {} to {} ops:", self.tree, self.original, self.ops.len())?;
+        for (step, op) in self.ops.iter().enumerate() {
+            writeln!(f, "  {:>4}  {}", step, op)?;
+        }
+        write!(f, "step {} ({}): {}", self.mismatch.step, self.mismatch.op, self.mismatch.message)
+    }
+}
+
+impl std::error::Error for Failure {}
+
+// applies ops to a fresh tree and a model side by side, checking the contents
+// and the invariants after every step
+pub fn run_ops<S: Subject>(ops: &[Op]) -> Result<(), Mismatch> {
+    let step = Cell::new(0);
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        let mut tree = S::default();
+        let mut model: BTreeMap<usize, usize> = BTreeMap::new();
+        for (i, &op) in ops.iter().enumerate() {
+            step.set(i);
+            let mismatch = |message: String| Err(Mismatch { step: i, op, message });
+            match op {
+                Op::Insert(key) => {
+                    tree.insert(key);
+                    *model.entry(key).or_default() += 1;
+                }
+                Op::Delete(key) => {
+                    let expected = match model.get_mut(&key) {
+                        Some(count) => {
+                            *count -= 1;
+                            if *count == 0 {
+                                model.remove(&key);
+                            }
+                            true
+                        }
+                        None => false,
+                    };
+                    let found = tree.delete(key);
+                    if found != expected {
+                        return mismatch(format!("delete returned {}, expected {}", found, expected));
+                    }
+                }
+                Op::Contains(key) => {
+                    let (found, expected) = (tree.contains(key), model.contains_key(&key));
+                    if found != expected {
+                        return mismatch(format!("contains returned {}, expected {}", found, expected));
+                    }
+                }
+                Op::Iterate => {}
+            }
+            let keys = tree.keys();
+            let expected = model.iter().flat_map(|(&key, &count)| std::iter::repeat_n(key, count)).collect::<Vec<_>>();
+            if keys != expected {
+                return mismatch(format!("holds {:?}, expected {:?}", keys, expected));
+            }
+            if let Err(e) = tree.validate() {
+                return mismatch(e);
+            }
+        }
+        Ok(())
+    }));
+    result.unwrap_or_else(|payload| {
+        let message = payload.downcast_ref::<&str>().map(|s| s.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic".to_string());
+        let i = step.get();
+        Err(Mismatch { step: i, op: ops[i], message: format!("panicked: {}", message) })
+    })
+}
+
+// runs ops and, if they fail, shrinks them to a minimal failing sequence
+pub fn check<S: Subject>(ops: &[Op]) -> Result<(), Failure> {
+    let mismatch = match run_ops::<S>(ops) {
+        Ok(()) => return Ok(()),
+        Err(mismatch) => mismatch,
+    };
+    let original = ops.len();
+    let (ops, mismatch) = shrink::<S>(ops.to_vec(), mismatch);
+    Err(Failure { tree: S::NAME, original, ops, mismatch })
+}
+
+// drops chunks of ops, halving the chunk size, then lowers keys one op at a time,
+// until neither makes the sequence any smaller
+pub fn shrink<S: Subject>(mut ops: Vec<Op>, mut mismatch: Mismatch) -> (Vec<Op>, Mismatch) {
+    loop {
+        // whatever comes after the failing step is never reached
+        ops.truncate(mismatch.step + 1);
+        let before = ops.clone();
+
+        let mut chunk = ops.len().div_ceil(2);
+        while chunk > 0 {
+            let mut start = 0;
+            while start < ops.len() {
+                let end = (start + chunk).min(ops.len());
+                let candidate = [&ops[..start], &ops[end..]].concat();
+                match run_ops::<S>(&candidate) {
+                    Err(found) => {
+                        ops = candidate;
+                        ops.truncate(found.step + 1);
+                        mismatch = found;
+                    }
+                    Ok(()) => start += chunk,
+                }
+            }
+            chunk /= 2;
+        }
+
+        for i in 0..ops.len() {
+            while let Some(key) = ops[i].key().filter(|&key| key > 0) {
+                let smaller = [0, key / 2, key - 1].into_iter().find_map(|smaller| {
+                    let mut candidate = ops.clone();
+                    candidate[i] = candidate[i].with_key(smaller);
+                    run_ops::<S>(&candidate).err().map(|found| (candidate, found))
+                });
+                let Some((candidate, found)) = smaller else { break };
+                ops = candidate;
+                mismatch = found;
+            }
+            if i >= mismatch.step {
+                break;
+            }
+        }
+
+        if ops == before {
+            return (ops, mismatch);
+        }
+    }
+}
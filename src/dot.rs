@@ -0,0 +1,202 @@
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::str::FromStr;
+
+use crate::shape::NodeView;
+
+// what the exporter needs beyond the shape: a printable key
+pub trait DotNode: NodeView {
+    fn key(&self) -> String;
+}
+
+// per-node values written under the key
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Annotation {
+    Depth,
+    Size,
+    Height,
+    // height of the right subtree minus height of the left one
+    Balance,
+}
+
+impl Annotation {
+    pub const ALL: [Annotation; 4] = [Annotation::Depth, Annotation::Size, Annotation::Height, Annotation::Balance];
+
+    pub fn key(&self) -> &'static str {
+        match self {
+            Annotation::Depth => "depth",
+            Annotation::Size => "size",
+            Annotation::Height => "height",
+            Annotation::Balance => "balance",
+        }
+    }
+}
+
+impl FromStr for Annotation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Annotation::ALL.into_iter()
+            .find(|annotation| annotation.key() == s)
+            .ok_or_else(|| format!("unknown annotation `{}`", s))
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct DotOptions {
+    null_leaves: bool,
+    parents: bool,
+    annotations: Vec<Annotation>,
+}
+
+impl DotOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // draw the empty child links, black NIL boxes for red-black trees
+    pub fn null_leaves(mut self, null_leaves: bool) -> Self {
+        self.null_leaves = null_leaves;
+        self
+    }
+
+    // dashed edges back along the parent pointers, broken ones in red
+    pub fn parents(mut self, parents: bool) -> Self {
+        self.parents = parents;
+        self
+    }
+
+    pub fn annotate(mut self, annotation: Annotation) -> Self {
+        if !self.annotations.contains(&annotation) {
+            self.annotations.push(annotation);
+        }
+        self
+    }
+}
+
+struct Entry<N> {
+    node: N,
+    depth: usize,
+    left: Option<usize>,
+    right: Option<usize>,
+    size: usize,
+    height: usize,
+}
+
+fn quote(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+pub fn to_dot<N: DotNode>(root: Option<N>, options: &DotOptions) -> String {
+    // pre-order on an explicit stack, a child always gets a higher index than its parent
+    let mut entries: Vec<Entry<N>> = Vec::new();
+    let mut stack = root.into_iter().map(|node| (node, 0, None)).collect::<Vec<(N, usize, Option<(usize, bool)>)>>();
+    while let Some((node, depth, parent)) = stack.pop() {
+        let index = entries.len();
+        if let Some((parent, is_left)) = parent {
+            let parent: &mut Entry<N> = &mut entries[parent];
+            if is_left { parent.left = Some(index) } else { parent.right = Some(index) }
+        }
+        stack.extend(node.right().map(|right| (right, depth + 1, Some((index, false)))));
+        stack.extend(node.left().map(|left| (left, depth + 1, Some((index, true)))));
+        entries.push(Entry { node, depth, left: None, right: None, size: 1, height: 1 });
+    }
+    // backwards, every subtree is done before its parent
+    for index in (0..entries.len()).rev() {
+        let child = |child: Option<usize>| child.map_or((0, 0), |c| (entries[c].size, entries[c].height));
+        let (left, right) = (child(entries[index].left), child(entries[index].right));
+        entries[index].size += left.0 + right.0;
+        entries[index].height += left.1.max(right.1);
+    }
+
+    let colored = entries.first().is_some_and(|entry| entry.node.red().is_some());
+    let ids = entries.iter().enumerate().map(|(index, entry)| (entry.node.id(), index)).collect::<HashMap<_, _>>();
+    let height = |child: Option<usize>| child.map_or(0, |c| entries[c].height) as isize;
+
+    let mut out = String::new();
+    writeln!(out, "digraph tree {{").unwrap();
+    writeln!(out, "    node [shape=circle, style=filled, fillcolor=white, fontname=\"Helvetica\"];").unwrap();
+    if !options.annotations.is_empty() {
+        writeln!(out, "    node [shape=ellipse];").unwrap();
+    }
+
+    for (index, entry) in entries.iter().enumerate() {
+        let mut label = quote(&entry.node.key());
+        for annotation in &options.annotations {
+            let value = match annotation {
+                Annotation::Depth => entry.depth as isize,
+                Annotation::Size => entry.size as isize,
+                Annotation::Height => entry.height as isize,
+                Annotation::Balance => height(entry.right) - height(entry.left),
+            };
+            write!(label, "\\n{} {}", annotation.key(), value).unwrap();
+        }
+        let fill = match entry.node.red() {
+            Some(true) => ", fillcolor=red, fontcolor=white",
+            Some(false) => ", fillcolor=black, fontcolor=white",
+            None => "",
+        };
+        writeln!(out, "    n{} [label=\"{}\"{}];", index, label, fill).unwrap();
+
+        for (side, child) in [("l", entry.left), ("r", entry.right)] {
+            match child {
+                Some(child) => writeln!(out, "    n{} -> n{};", index, child).unwrap(),
+                None if options.null_leaves && colored => {
+                    writeln!(out, "    nil{}{} [shape=box, label=\"NIL\", fontsize=8, width=0.3, height=0.2, fillcolor=black, fontcolor=white];", index, side).unwrap();
+                    writeln!(out, "    n{} -> nil{}{};", index, index, side).unwrap();
+                }
+                None if options.null_leaves => {
+                    writeln!(out, "    nil{}{} [shape=point];", index, side).unwrap();
+                    writeln!(out, "    n{} -> nil{}{};", index, index, side).unwrap();
+                }
+                // without a placeholder dot can't tell a lone left child from a lone right one
+                None if entry.left.is_some() || entry.right.is_some() => {
+                    writeln!(out, "    nil{}{} [shape=point, style=invis];", index, side).unwrap();
+                    writeln!(out, "    n{} -> nil{}{} [style=invis];", index, index, side).unwrap();
+                }
+                None => {}
+            }
+        }
+    }
+
+    if options.parents {
+        write_parents(&mut out, &entries, &ids);
+    }
+    writeln!(out, "}}").unwrap();
+    out
+}
+
+fn write_parents<N: DotNode>(out: &mut String, entries: &[Entry<N>], ids: &HashMap<usize, usize>) {
+    // the parent each node should point at
+    let mut expected = vec![None; entries.len()];
+    for (index, entry) in entries.iter().enumerate() {
+        for child in [entry.left, entry.right].into_iter().flatten() {
+            expected[child] = Some(index);
+        }
+    }
+
+    for (index, entry) in entries.iter().enumerate() {
+        let Some(parent) = entry.node.parent() else { continue };
+        match (parent, expected[index]) {
+            (None, None) => {}
+            (None, Some(_)) => {
+                writeln!(out, "    n{} [color=red, penwidth=3, xlabel=\"no parent\"];", index).unwrap();
+            }
+            // the red-black tree marks its root by pointing it at itself
+            (Some(parent), None) if parent.id() == entry.node.id() => {}
+            (Some(parent), expected) => match ids.get(&parent.id()) {
+                Some(&actual) if Some(actual) == expected => {
+                    writeln!(out, "    n{} -> n{} [style=dashed, color=blue, constraint=false];", index, actual).unwrap();
+                }
+                Some(&actual) => {
+                    writeln!(out, "    n{} -> n{} [style=dashed, color=red, constraint=false, label=\"wrong parent\"];", index, actual).unwrap();
+                }
+                // pointing at a node that is no longer in the tree
+                None => {
+                    writeln!(out, "    stray{} [label=\"{}\", style=dashed, color=red];", index, quote(&parent.key())).unwrap();
+                    writeln!(out, "    n{} -> stray{} [style=dashed, color=red, constraint=false, label=\"stray parent\"];", index, index).unwrap();
+                }
+            },
+        }
+    }
+}
@@ -1,10 +1,35 @@
 use std::ops::Add;
+use std::str::FromStr;
+use std::time::Duration;
 
+use serde::{Deserialize, Serialize};
+
+use crate::frames::{Frame, Recorder, Side, Snapshot};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Stats {
     comps: usize,
     ptr_read: usize,
     ptr_swap: usize,
     height: usize,
+    // wall-clock time of the operation, only filled in by timed runs
+    #[serde(default)]
+    nanos: usize,
+    // edges from the root to the node the operation found or placed, or where a search ended
+    #[serde(default)]
+    depth: usize,
+    // rotations done by a splay, only read by the amortized analysis and not kept in Data
+    #[serde(default)]
+    rotations: usize,
+    // step by step copies of the tree, only kept by stats made with `recording`
+    #[serde(skip)]
+    recorder: Option<Box<Recorder>>,
+}
+
+impl Default for Stats {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Stats {
@@ -14,13 +39,55 @@ impl Stats {
             ptr_read: 0,
             ptr_swap: 0,
             height: 0,
+            nanos: 0,
+            depth: 0,
+            rotations: 0,
+            recorder: None,
+        }
+    }
+
+    pub fn recording() -> Self {
+        Stats { recorder: Some(Box::default()), ..Self::new() }
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recorder.is_some()
+    }
+
+    // the closures only run while recording, so the trees pay nothing for their frames otherwise
+    pub fn frame(&mut self, caption: impl FnOnce() -> String, tree: impl FnOnce() -> Snapshot) {
+        if let Some(recorder) = &mut self.recorder {
+            recorder.frame(caption(), tree());
         }
     }
 
+    // the part of the tree above a detached subtree, with the way down to where it hangs
+    pub(crate) fn enter(&mut self, outer: impl FnOnce() -> (Snapshot, Vec<Side>)) {
+        if let Some(recorder) = &mut self.recorder {
+            let (outer, path) = outer();
+            recorder.enter(outer, path);
+        }
+    }
+
+    pub(crate) fn leave(&mut self) {
+        if let Some(recorder) = &mut self.recorder {
+            recorder.leave();
+        }
+    }
+
+    pub fn take_frames(&mut self) -> Vec<Frame> {
+        self.recorder.as_mut().map_or_else(Vec::new, |recorder| recorder.take())
+    }
+
     pub fn comp(&mut self) {
         self.comps += 1;
     }
 
+    // n comparisons at once, made where they could only be counted afterwards
+    pub fn comps(&mut self, n: usize) {
+        self.comps += n;
+    }
+
     pub fn read(&mut self) {
         self.ptr_read += 1;
     }
@@ -32,12 +99,38 @@ impl Stats {
     pub fn height(&mut self, height: usize) {
         self.height = height;
     }
+
+    pub fn time(&mut self, elapsed: Duration) {
+        self.nanos = elapsed.as_nanos() as usize;
+    }
+
+    pub fn depth(&mut self, depth: usize) {
+        self.depth = depth;
+    }
+
+    pub fn rotate(&mut self) {
+        self.rotations += 1;
+    }
+
+    pub fn rotations(&self) -> usize {
+        self.rotations
+    }
 }
 
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Data {
     count: usize,
     sum: Stats,
     max: Stats,
+    // wall-clock time of each whole phase, one sample per repetition
+    #[serde(default)]
+    phase_nanos: Vec<usize>,
+}
+
+impl Default for Data {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Data {
@@ -46,6 +139,7 @@ impl Data {
             count: 0,
             sum: Stats::new(),
             max: Stats::new(),
+            phase_nanos: Vec::new(),
         }
     }
     pub fn add_stat(&mut self, stats: Stats) {
@@ -55,6 +149,8 @@ impl Data {
         self.sum.ptr_read += stats.ptr_read;
         self.sum.ptr_swap += stats.ptr_swap;
         self.sum.height += stats.height;
+        self.sum.nanos += stats.nanos;
+        self.sum.depth += stats.depth;
 
         if stats.comps > self.max.comps {
             self.max.comps = stats.comps;
@@ -68,6 +164,45 @@ impl Data {
         if stats.height > self.max.height {
             self.max.height = stats.height;
         }
+        if stats.nanos > self.max.nanos {
+            self.max.nanos = stats.nanos;
+        }
+        if stats.depth > self.max.depth {
+            self.max.depth = stats.depth;
+        }
+    }
+
+    pub fn add_phase(&mut self, elapsed: Duration) {
+        self.phase_nanos.push(elapsed.as_nanos() as usize);
+    }
+
+    pub fn phase_nanos(&self) -> &[usize] {
+        &self.phase_nanos
+    }
+
+    // mean time of a single operation, measured around each operation
+    pub fn avg_nanos(&self) -> f64 {
+        self.sum.nanos as f64 / self.count as f64
+    }
+
+    // phase time per operation, averaged over the repetitions left after outlier rejection
+    pub fn phase_nanos_per_op(&self) -> f64 {
+        let kept = reject_outliers(&self.phase_nanos);
+        if kept.is_empty() {
+            return f64::NAN;
+        }
+        let ops_per_phase = self.count as f64 / self.phase_nanos.len() as f64;
+        kept.iter().sum::<f64>() / kept.len() as f64 / ops_per_phase
+    }
+
+    // the per-repetition values behind phase_nanos_per_op, for error bands
+    pub fn phase_nanos_samples(&self) -> Vec<f64> {
+        let ops_per_phase = self.count as f64 / self.phase_nanos.len() as f64;
+        reject_outliers(&self.phase_nanos).into_iter().map(|nanos| nanos / ops_per_phase).collect()
+    }
+
+    pub fn avg_depth(&self) -> f64 {
+        self.sum.depth as f64 / self.count as f64
     }
 
     pub fn avg(&self) -> (f64, f64, f64, f64) {
@@ -83,9 +218,42 @@ impl Data {
     pub fn max(&self) -> &Stats {
         &self.max
     }
+
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    // flat form used by the csv results files, phase_nanos goes in a column of its own
+    pub const FIELDS: [&'static str; 13] = [
+        "count",
+        "sum_comps", "sum_ptr_read", "sum_ptr_swap", "sum_height", "sum_nanos", "sum_depth",
+        "max_comps", "max_ptr_read", "max_ptr_swap", "max_height", "max_nanos", "max_depth",
+    ];
+
+    pub fn to_fields(&self) -> [usize; 13] {
+        [
+            self.count,
+            self.sum.comps, self.sum.ptr_read, self.sum.ptr_swap, self.sum.height, self.sum.nanos, self.sum.depth,
+            self.max.comps, self.max.ptr_read, self.max.ptr_swap, self.max.height, self.max.nanos, self.max.depth,
+        ]
+    }
+
+    pub fn from_fields(fields: [usize; 13], phase_nanos: Vec<usize>) -> Self {
+        let [count, comps, ptr_read, ptr_swap, height, nanos, depth,
+            max_comps, max_ptr_read, max_ptr_swap, max_height, max_nanos, max_depth] = fields;
+        Data {
+            count,
+            sum: Stats { comps, ptr_read, ptr_swap, height, nanos, depth, rotations: 0, recorder: None },
+            max: Stats {
+                comps: max_comps, ptr_read: max_ptr_read, ptr_swap: max_ptr_swap,
+                height: max_height, nanos: max_nanos, depth: max_depth, rotations: 0, recorder: None,
+            },
+            phase_nanos,
+        }
+    }
 }
 
-pub fn divide_into(data: Data, dataset: &mut Vec<Vec<f64>>) {
+pub fn divide_into(data: Data, dataset: &mut [Vec<f64>]) {
     let (sum, ptr_read, ptr_swap, height) = data.avg();
     dataset[0].push(sum);
     dataset[1].push(ptr_read);
@@ -115,6 +283,181 @@ impl Add for Data {
         self.max.ptr_swap = self.max.ptr_swap.max(other.max.ptr_swap);
         self.max.height = self.max.height.max(other.max.height);
 
+        self.sum.nanos += other.sum.nanos;
+        self.max.nanos = self.max.nanos.max(other.max.nanos);
+        self.phase_nanos.extend(other.phase_nanos);
+
+        self.sum.depth += other.sum.depth;
+        self.max.depth = self.max.depth.max(other.max.depth);
+
         self
     }
-}
\ No newline at end of file
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Metric {
+    AvgComps,
+    AvgPtrRead,
+    AvgPtrSwap,
+    AvgHeight,
+    MaxComps,
+    MaxPtrRead,
+    MaxPtrSwap,
+    MaxHeight,
+    AvgNanos,
+    MaxNanos,
+    PhaseNanos,
+    AvgDepth,
+    MaxDepth,
+}
+
+impl Metric {
+    pub const ALL: [Metric; 13] = [
+        Metric::AvgComps,
+        Metric::AvgPtrRead,
+        Metric::AvgPtrSwap,
+        Metric::AvgHeight,
+        Metric::MaxComps,
+        Metric::MaxPtrRead,
+        Metric::MaxPtrSwap,
+        Metric::MaxHeight,
+        Metric::AvgNanos,
+        Metric::MaxNanos,
+        Metric::PhaseNanos,
+        Metric::AvgDepth,
+        Metric::MaxDepth,
+    ];
+
+    // the operation counts, what the charts show unless asked otherwise
+    pub const COUNTERS: [Metric; 10] = [
+        Metric::AvgComps,
+        Metric::AvgPtrRead,
+        Metric::AvgPtrSwap,
+        Metric::AvgHeight,
+        Metric::MaxComps,
+        Metric::MaxPtrRead,
+        Metric::MaxPtrSwap,
+        Metric::MaxHeight,
+        Metric::AvgDepth,
+        Metric::MaxDepth,
+    ];
+
+    // same labels the charts have always used
+    pub fn name(&self) -> &'static str {
+        match self {
+            Metric::AvgComps => "avg comps",
+            Metric::AvgPtrRead => "avg ptr_read",
+            Metric::AvgPtrSwap => "avg ptr_swap",
+            Metric::AvgHeight => "avg height",
+            Metric::MaxComps => "max comps",
+            Metric::MaxPtrRead => "max ptr_read",
+            Metric::MaxPtrSwap => "max ptr_swap",
+            Metric::MaxHeight => "max height",
+            Metric::AvgNanos => "avg ns/op",
+            Metric::MaxNanos => "max ns/op",
+            Metric::PhaseNanos => "phase ns/op",
+            Metric::AvgDepth => "avg depth",
+            Metric::MaxDepth => "max depth",
+        }
+    }
+
+    // name used in config files and on the command line
+    pub fn key(&self) -> &'static str {
+        match self {
+            Metric::AvgComps => "avg_comps",
+            Metric::AvgPtrRead => "avg_ptr_read",
+            Metric::AvgPtrSwap => "avg_ptr_swap",
+            Metric::AvgHeight => "avg_height",
+            Metric::MaxComps => "max_comps",
+            Metric::MaxPtrRead => "max_ptr_read",
+            Metric::MaxPtrSwap => "max_ptr_swap",
+            Metric::MaxHeight => "max_height",
+            Metric::AvgNanos => "avg_nanos",
+            Metric::MaxNanos => "max_nanos",
+            Metric::PhaseNanos => "phase_nanos",
+            Metric::AvgDepth => "avg_depth",
+            Metric::MaxDepth => "max_depth",
+        }
+    }
+
+    pub fn value(&self, data: &Data) -> f64 {
+        let (comps, ptr_read, ptr_swap, height) = data.avg();
+        match self {
+            Metric::AvgComps => comps,
+            Metric::AvgPtrRead => ptr_read,
+            Metric::AvgPtrSwap => ptr_swap,
+            Metric::AvgHeight => height,
+            Metric::MaxComps => data.max.comps as f64,
+            Metric::MaxPtrRead => data.max.ptr_read as f64,
+            Metric::MaxPtrSwap => data.max.ptr_swap as f64,
+            Metric::MaxHeight => data.max.height as f64,
+            Metric::AvgNanos => data.avg_nanos(),
+            Metric::MaxNanos => data.max.nanos as f64,
+            Metric::PhaseNanos => data.phase_nanos_per_op(),
+            Metric::AvgDepth => data.avg_depth(),
+            Metric::MaxDepth => data.max.depth as f64,
+        }
+    }
+
+    // the metric holding the worst case of what this one averages
+    pub fn max_counterpart(&self) -> Option<Metric> {
+        match self {
+            Metric::AvgComps => Some(Metric::MaxComps),
+            Metric::AvgPtrRead => Some(Metric::MaxPtrRead),
+            Metric::AvgPtrSwap => Some(Metric::MaxPtrSwap),
+            Metric::AvgHeight => Some(Metric::MaxHeight),
+            Metric::AvgNanos => Some(Metric::MaxNanos),
+            Metric::AvgDepth => Some(Metric::MaxDepth),
+            _ => None,
+        }
+    }
+
+    // height is measured by walking the whole tree, so it is only worth doing when asked for
+    pub fn needs_height(&self) -> bool {
+        matches!(self, Metric::AvgHeight | Metric::MaxHeight)
+    }
+
+    // the library structures have no height or depths to report
+    pub fn tree_only(&self) -> bool {
+        matches!(self, Metric::AvgHeight | Metric::MaxHeight | Metric::AvgDepth | Metric::MaxDepth)
+    }
+
+    pub fn needs_timing(&self) -> bool {
+        matches!(self, Metric::AvgNanos | Metric::MaxNanos | Metric::PhaseNanos)
+    }
+}
+
+// samples further than this many scaled median absolute deviations from the median are dropped
+pub const OUTLIER_THRESHOLD: f64 = 3.5;
+
+pub fn reject_outliers(samples: &[usize]) -> Vec<f64> {
+    let median = |values: &mut Vec<f64>| {
+        values.sort_by(|a, b| a.total_cmp(b));
+        let mid = values.len() / 2;
+        if values.len().is_multiple_of(2) { (values[mid - 1] + values[mid]) / 2.0 } else { values[mid] }
+    };
+    if samples.len() < 3 {
+        return samples.iter().map(|&x| x as f64).collect();
+    }
+
+    let mut values = samples.iter().map(|&x| x as f64).collect::<Vec<_>>();
+    let center = median(&mut values);
+    let mut deviations = values.iter().map(|x| (x - center).abs()).collect::<Vec<_>>();
+    // 1.4826 makes the MAD comparable to a standard deviation for normal data
+    let mad = median(&mut deviations) * 1.4826;
+    if mad == 0.0 {
+        return values;
+    }
+    values.into_iter().filter(|x| (x - center).abs() / mad <= OUTLIER_THRESHOLD).collect()
+}
+
+impl FromStr for Metric {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Metric::ALL.into_iter()
+            .find(|metric| metric.key() == s)
+            .ok_or_else(|| format!("unknown metric `{}`", s))
+    }
+}
@@ -0,0 +1,178 @@
+use std::fmt::{Display, Formatter};
+
+use crate::chart::{draw_chart, ChartError, ChartOptions};
+use crate::experiment::Metric;
+use crate::results::Saved;
+use crate::structure::StructureKind;
+
+// growth models a metric is fitted against, as y = a * f(n) + b
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Model {
+    Log,
+    LogSquared,
+    Sqrt,
+    Linear,
+}
+
+impl Model {
+    pub const ALL: [Model; 4] = [Model::Log, Model::LogSquared, Model::Sqrt, Model::Linear];
+
+    pub fn f(&self, n: f64) -> f64 {
+        match self {
+            Model::Log => n.log2(),
+            Model::LogSquared => n.log2().powi(2),
+            Model::Sqrt => n.sqrt(),
+            Model::Linear => n,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Model::Log => "log2 n",
+            Model::LogSquared => "log2^2 n",
+            Model::Sqrt => "sqrt n",
+            Model::Linear => "n",
+        }
+    }
+}
+
+// below this R² even the best model explains too little of the values to name it
+pub const MIN_R2: f64 = 0.9;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Fit {
+    pub model: Model,
+    pub a: f64,
+    pub b: f64,
+    pub r2: f64,
+}
+
+impl Fit {
+    // least squares fit of y = a * f(n) + b, None with fewer than two usable points
+    pub fn new(model: Model, ns: &[usize], ys: &[f64]) -> Option<Self> {
+        let points = ns.iter().zip(ys)
+            .filter(|(_, y)| y.is_finite())
+            .map(|(&n, &y)| (model.f(n as f64), y))
+            .collect::<Vec<_>>();
+        if points.len() < 2 {
+            return None;
+        }
+
+        let count = points.len() as f64;
+        let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / count;
+        let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / count;
+        let var_x = points.iter().map(|(x, _)| (x - mean_x).powi(2)).sum::<f64>();
+        if var_x == 0.0 {
+            return None;
+        }
+        let cov = points.iter().map(|(x, y)| (x - mean_x) * (y - mean_y)).sum::<f64>();
+        let a = cov / var_x;
+        let b = mean_y - a * mean_x;
+
+        let ss_res = points.iter().map(|(x, y)| (y - (a * x + b)).powi(2)).sum::<f64>();
+        let ss_tot = points.iter().map(|(_, y)| (y - mean_y).powi(2)).sum::<f64>();
+        let r2 = if ss_tot == 0.0 { 1.0 } else { 1.0 - ss_res / ss_tot };
+
+        Some(Fit { model, a, b, r2 })
+    }
+
+    // the model with the highest R², ties go to the slower growing one
+    pub fn best(ns: &[usize], ys: &[f64]) -> Option<Self> {
+        Model::ALL.iter()
+            .filter_map(|&model| Fit::new(model, ns, ys))
+            .fold(None, |best: Option<Fit>, fit| match best {
+                Some(best) if best.r2 >= fit.r2 => Some(best),
+                _ => Some(fit),
+            })
+    }
+
+    // best, but None unless it reaches MIN_R2
+    pub fn good(ns: &[usize], ys: &[f64]) -> Option<Self> {
+        Self::best(ns, ys).filter(|fit| fit.r2 >= MIN_R2)
+    }
+
+    pub fn eval(&self, n: f64) -> f64 {
+        self.a * self.model.f(n) + self.b
+    }
+}
+
+impl Display for Fit {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        let sign = if self.b < 0.0 { '-' } else { '+' };
+        write!(f, "{:.3} * {} {} {:.3} (R2 = {:.4})", self.a, self.model.name(), sign, self.b.abs(), self.r2)
+    }
+}
+
+// textbook bounds drawn next to the measured values
+pub struct Bound {
+    pub name: &'static str,
+    pub f: fn(f64) -> f64,
+}
+
+pub fn bounds(structure: StructureKind, metric: Metric) -> Vec<Bound> {
+    let mut bounds = Vec::new();
+    let height_like = metric.tree_only() || matches!(metric, Metric::AvgComps | Metric::MaxComps);
+
+    if structure == StructureKind::RbTree && height_like {
+        bounds.push(Bound { name: "2 log2(n+1)", f: |n| 2.0 * (n + 1.0).log2() });
+    }
+    // no binary search tree gets shorter than this
+    if structure.is_tree() && matches!(metric, Metric::AvgHeight | Metric::MaxHeight) {
+        bounds.push(Bound { name: "log2(n+1)", f: |n| (n + 1.0).log2() });
+    }
+    bounds
+}
+
+pub struct FitReport {
+    pub structure: StructureKind,
+    pub phase: &'static str,
+    pub metric: Metric,
+    // None when no model reaches MIN_R2
+    pub best: Option<Fit>,
+    pub fits: Vec<Fit>,
+}
+
+pub fn fit_all(saved: &Saved, metrics: &[Metric]) -> Vec<FitReport> {
+    let mut reports = Vec::new();
+    for results in &saved.results {
+        for (phase, data) in [("insert", &results.insert), ("delete", &results.delete)] {
+            for &metric in metrics {
+                if metric.tree_only() && !results.structure.is_tree() {
+                    continue;
+                }
+                let ys = data.iter().map(|d| metric.value(d)).collect::<Vec<_>>();
+                let fits = Model::ALL.iter().filter_map(|&m| Fit::new(m, &results.sizes, &ys)).collect::<Vec<_>>();
+                if !fits.is_empty() {
+                    let best = Fit::good(&results.sizes, &ys);
+                    reports.push(FitReport { structure: results.structure, phase, metric, best, fits });
+                }
+            }
+        }
+    }
+    reports
+}
+
+// the measured values with the best fit, if there is a good one, and the bounds on top, one chart per report
+pub fn draw_fits(saved: &Saved, reports: &[FitReport], options: &ChartOptions) -> Result<(), ChartError> {
+    for report in reports {
+        let Some(results) = saved.results.iter().find(|r| r.structure == report.structure) else { continue };
+        let data = if report.phase == "insert" { &results.insert } else { &results.delete };
+        let sizes = &results.sizes;
+
+        let mut lines = vec![data.iter().map(|d| report.metric.value(d)).collect::<Vec<_>>()];
+        let mut names = vec![report.metric.name().to_string()];
+        if let Some(best) = &report.best {
+            lines.push(sizes.iter().map(|&n| best.eval(n as f64)).collect());
+            names.push(format!("fit: {}", best));
+        }
+        for bound in bounds(report.structure, report.metric) {
+            lines.push(sizes.iter().map(|&n| (bound.f)(n as f64)).collect());
+            names.push(bound.name.to_string());
+        }
+
+        let name = format!("{}_{}_{}_{}_fit", saved.metadata.name, report.structure, report.phase, report.metric.key());
+        let options = options.clone().y_label(report.metric.name());
+        draw_chart(lines, names.iter().map(String::as_str).collect(), sizes.iter().copied(), &name, &options, |_, y| y)?;
+    }
+    Ok(())
+}
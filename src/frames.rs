@@ -0,0 +1,340 @@
+use std::fmt::{Debug, Display, Formatter};
+use std::path::PathBuf;
+
+use plotters::coord::Shift;
+use plotters::prelude::{BitMapBackend, Circle, DrawingArea, DrawingBackend, IntoDrawingArea, IntoFont, PathElement, SVGBackend, Text, BLACK, RED, WHITE};
+use plotters::style::text_anchor::{HPos, Pos, VPos};
+use plotters::style::ShapeStyle;
+
+use crate::bin_tree::BinTree;
+use crate::chart::{ChartError, ChartOptions, Format};
+use crate::dot::{to_dot, DotNode, DotOptions};
+use crate::experiment::Stats;
+use crate::printer::{write_tree, PrintOptions};
+use crate::rb_tree;
+use crate::shape::NodeView;
+use crate::splay_tree::SplayTree;
+use crate::structure::StructureKind;
+
+// how the operations of a tree whose keys need not be Debug write them: the framed operations
+// pass debug_key and get frames, the plain ones pass None and record none
+pub(crate) type Keys<T> = Option<fn(&T) -> String>;
+
+pub(crate) fn debug_key<T: Debug>(key: &T) -> String {
+    format!("{:?}", key)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Side {
+    Left,
+    Right,
+}
+
+// owned copy of a tree's keys, colors and links, nodes[0] is the root
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Snapshot {
+    nodes: Vec<SnapNode>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct SnapNode {
+    key: String,
+    red: Option<bool>,
+    left: Option<usize>,
+    right: Option<usize>,
+}
+
+// one step of an operation: the whole tree right after it and what was done
+#[derive(Clone, Debug, PartialEq)]
+pub struct Frame {
+    pub caption: String,
+    pub tree: Snapshot,
+}
+
+// kept by Stats while recording; trees whose operations work on detached subtrees
+// leave the rest of the tree here so every frame still shows all of it
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Recorder {
+    frames: Vec<Frame>,
+    context: Vec<(Snapshot, Vec<Side>)>,
+}
+
+impl Recorder {
+    pub(crate) fn frame(&mut self, caption: String, subtree: Snapshot) {
+        let tree = self.context.iter().rev().fold(subtree, |tree, (outer, path)| outer.clone().graft(path, tree));
+        self.frames.push(Frame { caption, tree });
+    }
+
+    pub(crate) fn enter(&mut self, outer: Snapshot, path: Vec<Side>) {
+        self.context.push((outer, path));
+    }
+
+    pub(crate) fn leave(&mut self) {
+        self.context.pop();
+    }
+
+    pub(crate) fn take(&mut self) -> Vec<Frame> {
+        std::mem::take(&mut self.frames)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct SnapRef<'a> {
+    snapshot: &'a Snapshot,
+    index: usize,
+}
+
+impl NodeView for SnapRef<'_> {
+    fn left(&self) -> Option<Self> {
+        self.snapshot.nodes[self.index].left.map(|index| SnapRef { snapshot: self.snapshot, index })
+    }
+
+    fn right(&self) -> Option<Self> {
+        self.snapshot.nodes[self.index].right.map(|index| SnapRef { snapshot: self.snapshot, index })
+    }
+
+    fn red(&self) -> Option<bool> {
+        self.snapshot.nodes[self.index].red
+    }
+
+    fn id(&self) -> usize {
+        self.index
+    }
+}
+
+impl DotNode for SnapRef<'_> {
+    fn key(&self) -> String {
+        self.snapshot.nodes[self.index].key.clone()
+    }
+}
+
+impl Snapshot {
+    pub fn of<N: DotNode>(root: Option<N>) -> Self {
+        Self::of_with(root, N::key)
+    }
+
+    // of for keys without Debug, written by key instead
+    pub(crate) fn of_with<N: NodeView>(root: Option<N>, key: impl Fn(&N) -> String) -> Self {
+        let mut nodes: Vec<SnapNode> = Vec::new();
+        let mut stack = root.into_iter().map(|node| (node, None)).collect::<Vec<(N, Option<(usize, Side)>)>>();
+        while let Some((node, parent)) = stack.pop() {
+            let index = nodes.len();
+            match parent {
+                Some((parent, Side::Left)) => nodes[parent].left = Some(index),
+                Some((parent, Side::Right)) => nodes[parent].right = Some(index),
+                None => {}
+            }
+            stack.extend(node.right().map(|right| (right, Some((index, Side::Right)))));
+            stack.extend(node.left().map(|left| (left, Some((index, Side::Left)))));
+            nodes.push(SnapNode { key: key(&node), red: node.red(), left: None, right: None });
+        }
+        Snapshot { nodes }
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn root(&self) -> Option<SnapRef<'_>> {
+        (!self.nodes.is_empty()).then_some(SnapRef { snapshot: self, index: 0 })
+    }
+
+    // hangs subtree on the empty link at the end of path
+    fn graft(mut self, path: &[Side], subtree: Snapshot) -> Snapshot {
+        if subtree.is_empty() {
+            return self;
+        }
+        if self.is_empty() {
+            return subtree;
+        }
+        let offset = self.nodes.len();
+        let mut node = 0;
+        for (i, side) in path.iter().enumerate() {
+            let link = match side {
+                Side::Left => &mut self.nodes[node].left,
+                Side::Right => &mut self.nodes[node].right,
+            };
+            match *link {
+                Some(next) if i + 1 < path.len() => node = next,
+                _ => {
+                    *link = Some(offset);
+                    break;
+                }
+            }
+        }
+        self.nodes.extend(subtree.nodes.into_iter().map(|node| SnapNode {
+            left: node.left.map(|i| i + offset),
+            right: node.right.map(|i| i + offset),
+            ..node
+        }));
+        self
+    }
+
+    pub fn to_dot(&self, options: &DotOptions) -> String {
+        to_dot(self.root(), options)
+    }
+
+    // x is the in-order rank, y the depth
+    fn layout(&self) -> Vec<(usize, usize)> {
+        let mut positions = vec![(0, 0); self.nodes.len()];
+        let mut rank = 0;
+        let mut stack = Vec::new();
+        let mut current = self.root().map(|root| (root.index, 0));
+        while current.is_some() || !stack.is_empty() {
+            while let Some((index, depth)) = current {
+                stack.push((index, depth));
+                current = self.nodes[index].left.map(|left| (left, depth + 1));
+            }
+            let (index, depth) = stack.pop().unwrap();
+            positions[index] = (rank, depth);
+            rank += 1;
+            current = self.nodes[index].right.map(|right| (right, depth + 1));
+        }
+        positions
+    }
+
+    fn height(&self) -> usize {
+        self.layout().iter().map(|&(_, depth)| depth + 1).max().unwrap_or(0)
+    }
+}
+
+// sideways, the same way the trees' Debug printers draw
+impl Display for Snapshot {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write_tree(f, self.root(), &PrintOptions::default())
+    }
+}
+
+// the frames of every insert and then every delete, each operation opening with the tree before it
+pub fn animate(structure: StructureKind, inserts: &[usize], deletes: &[usize]) -> Option<Vec<Frame>> {
+    fn run<T>(
+        mut tree: T,
+        inserts: &[usize],
+        deletes: &[usize],
+        snapshot: impl Fn(&T) -> Snapshot,
+        op: impl Fn(&mut T, bool, usize, &mut Stats),
+    ) -> Vec<Frame> {
+        let mut stats = Stats::recording();
+        let mut frames = Vec::new();
+        let ops = inserts.iter().map(|&x| (true, x)).chain(deletes.iter().map(|&x| (false, x)));
+        for (insert, x) in ops {
+            let caption = format!("{} {}", if insert { "insert" } else { "delete" }, x);
+            frames.push(Frame { caption, tree: snapshot(&tree) });
+            op(&mut tree, insert, x, &mut stats);
+            frames.extend(stats.take_frames());
+        }
+        frames.push(Frame { caption: "done".to_string(), tree: snapshot(&tree) });
+        frames
+    }
+
+    let frames = match structure {
+        StructureKind::BinTree => run(BinTree::new(), inserts, deletes, BinTree::snapshot, |tree, insert, x, stats| {
+            if insert { tree.insert_framed(x, stats) } else { tree.delete_framed(x, stats); }
+        }),
+        StructureKind::RbTree => run(rb_tree::BinTree::new(), inserts, deletes, rb_tree::BinTree::snapshot, |tree, insert, x, stats| {
+            if insert { tree.insert(x, stats) } else { tree.delete(x, stats); }
+        }),
+        StructureKind::SplayTree => run(SplayTree::new(), inserts, deletes, SplayTree::snapshot, |tree, insert, x, stats| {
+            if insert { tree.insert_framed(x, stats) } else { tree.delete_framed(x, stats); }
+        }),
+        StructureKind::BTreeSet | StructureKind::RbTreeCrate => return None,
+    };
+    Some(frames)
+}
+
+// all frames share one scale, so nodes only move when the tree changes
+struct Scale {
+    columns: usize,
+    rows: usize,
+}
+
+fn draw_frame<DB: DrawingBackend>(area: &DrawingArea<DB, Shift>, frame: &Frame, scale: &Scale, options: &ChartOptions) -> Result<(), String> {
+    let font = options.font_name();
+    area.fill(&WHITE).map_err(|e| e.to_string())?;
+    let inner = area.titled(&frame.caption, (font, 30)).map_err(|e| e.to_string())?;
+    let (width, height) = inner.dim_in_pixel();
+    let step_x = width as f64 / (scale.columns + 1) as f64;
+    let step_y = height as f64 / (scale.rows + 1) as f64;
+    let radius = (step_x.min(step_y) * 0.4).clamp(4.0, 24.0) as i32;
+    let positions = frame.tree.layout();
+    let pixel = |(x, y): (usize, usize)| (((x + 1) as f64 * step_x) as i32, ((y + 1) as f64 * step_y) as i32);
+
+    for (index, node) in frame.tree.nodes.iter().enumerate() {
+        for child in [node.left, node.right].into_iter().flatten() {
+            let edge = PathElement::new(vec![pixel(positions[index]), pixel(positions[child])], BLACK);
+            inner.draw(&edge).map_err(|e| e.to_string())?;
+        }
+    }
+    let centered = Pos::new(HPos::Center, VPos::Center);
+    for (index, node) in frame.tree.nodes.iter().enumerate() {
+        let center = pixel(positions[index]);
+        let (fill, text) = match node.red {
+            Some(true) => (RED, WHITE),
+            Some(false) => (BLACK, WHITE),
+            None => (WHITE, BLACK),
+        };
+        inner.draw(&Circle::new(center, radius, ShapeStyle::from(fill).filled())).map_err(|e| e.to_string())?;
+        inner.draw(&Circle::new(center, radius, BLACK)).map_err(|e| e.to_string())?;
+        let style = (font, radius as f64).into_font().color(&text).pos(centered);
+        inner.draw(&Text::new(node.key.clone(), center, style)).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+fn scale(frames: &[Frame]) -> Scale {
+    Scale {
+        columns: frames.iter().map(|frame| frame.tree.len()).max().unwrap_or(0),
+        rows: frames.iter().map(|frame| frame.tree.height()).max().unwrap_or(0),
+    }
+}
+
+// one file per frame, {name}_000 and up; the terminal gets them printed one after another
+pub fn draw_frames(frames: &[Frame], name: &str, options: &ChartOptions) -> Result<Vec<PathBuf>, ChartError> {
+    if frames.is_empty() {
+        return Err(ChartError::Empty(name.to_string()));
+    }
+    let scale = scale(frames);
+    let mut files = Vec::new();
+    for (i, frame) in frames.iter().enumerate() {
+        if options.out_format() == Format::Terminal {
+            println!("{}\n{}", frame.caption, frame.tree);
+            continue;
+        }
+        let file = options.clone().file_name(format!("{}_{:03}", name, i)).path(name);
+        std::fs::create_dir_all(options.out_dir()).map_err(|e| ChartError::Io(file.clone(), e))?;
+        match options.out_format() {
+            Format::Png => draw_single(&BitMapBackend::new(&file, options.out_size()).into_drawing_area(), frame, &scale, options),
+            Format::Svg => draw_single(&SVGBackend::new(&file, options.out_size()).into_drawing_area(), frame, &scale, options),
+            Format::Terminal => unreachable!(),
+        }.map_err(|e| ChartError::Draw(file.clone(), e))?;
+        files.push(file);
+    }
+    Ok(files)
+}
+
+fn draw_single<DB: DrawingBackend>(area: &DrawingArea<DB, Shift>, frame: &Frame, scale: &Scale, options: &ChartOptions) -> Result<(), String> {
+    draw_frame(area, frame, scale, options)?;
+    area.present().map_err(|e| e.to_string())
+}
+
+// every frame in one animated gif, delay is the time each one stays up
+pub fn draw_gif(frames: &[Frame], name: &str, options: &ChartOptions, delay_ms: u32) -> Result<PathBuf, ChartError> {
+    if frames.is_empty() {
+        return Err(ChartError::Empty(name.to_string()));
+    }
+    let file = options.out_dir().join(format!("{}.gif", name));
+    std::fs::create_dir_all(options.out_dir()).map_err(|e| ChartError::Io(file.clone(), e))?;
+    let backend = BitMapBackend::gif(&file, options.out_size(), delay_ms).map_err(|e| ChartError::Draw(file.clone(), e.to_string()))?;
+    let area = backend.into_drawing_area();
+    let scale = scale(frames);
+    for frame in frames {
+        draw_frame(&area, frame, &scale, options).map_err(|e| ChartError::Draw(file.clone(), e))?;
+        // each present adds a frame to the gif
+        area.present().map_err(|e| ChartError::Draw(file.clone(), e.to_string()))?;
+    }
+    Ok(file)
+}
@@ -4,10 +4,27 @@ pub mod rb_tree;
 pub mod experiment;
 pub mod chart;
 pub mod splay_tree;
+pub mod structure;
+pub mod workload;
+pub mod config;
+pub mod runner;
+pub mod results;
+pub mod checkpoint;
+pub mod fit;
+pub mod baseline;
+pub mod amortized;
+pub mod shape;
+pub mod report;
+pub mod dot;
+pub mod frames;
+pub mod printer;
+pub mod validate;
+pub mod differential;
+pub mod live;
 
 #[cfg(test)]
 mod tests {
-    use rand::distributions::Uniform;
+    use rand::distr::Uniform;
     use rand::prelude::{Distribution, SliceRandom};
     use rand::Rng;
     use super::*;
@@ -16,7 +33,7 @@ mod tests {
     #[test]
     fn test_bin_tree_rand() {
         let n = 50usize;
-        let mut rng = rand::thread_rng();
+        let mut rng = rand::rng();
         let range = Uniform::new(0, 2 * n - 1).unwrap();
         let mut tree = bin_tree::BinTree::new();
         let mut elements = Vec::new();
@@ -28,16 +45,18 @@ mod tests {
             elements.push(x);
             println!("height: {:?}", tree.height2());
             println!("{:?}", tree);
+            tree.validate().unwrap();
         }
 
         for _i in 0..n {
-            let index = rng.gen_range(0..elements.len());
+            let index = rng.random_range(0..elements.len());
             // println!("index: {:?}", index);
             let x = elements.remove(index);
             println!("deleting: {:?}", x);
             tree.delete(x, &mut stats);
             println!("height: {:?}", tree.height2());
             println!("{:?}", tree);
+            tree.validate().unwrap();
         }
     }
 
@@ -45,29 +64,31 @@ mod tests {
     fn test_bin_tree_inc() {
         let n = 50usize;
         let mut tree = bin_tree::BinTree::new();
-        let ref mut stats = experiment::Stats::new();
+        let stats = &mut experiment::Stats::new();
         for i in 0..n {
             println!("inserting: {:?}", i);
             tree.insert(i, stats);
             println!("{:?}", tree.height());
             println!("{:?}", tree);
+            tree.validate().unwrap();
         }
         println!("{:?}", tree);
         let mut elements = (0..n).collect::<Vec<_>>();
-        elements.shuffle(&mut rand::thread_rng());
+        elements.shuffle(&mut rand::rng());
 
         for i in elements {
             println!("deleting: {:?}", i);
             tree.delete(i, stats);
             println!("height: {:?}", tree.height());
             println!("{:?}", tree);
+            tree.validate().unwrap();
         }
     }
 
     #[test]
     fn test_rb_tree_rand() {
         let n = 50usize;
-        let mut rng = rand::thread_rng();
+        let mut rng = rand::rng();
         let range = Uniform::new(0, 2 * n - 1).unwrap();
         let mut tree = rb_tree::BinTree::new();
         let mut elements = Vec::new();
@@ -79,16 +100,18 @@ mod tests {
             elements.push(x);
             println!("height: {:?}", tree.height());
             println!("{:?}", tree);
+            tree.validate().unwrap();
         }
 
         for _i in 0..n {
-            let index = rng.gen_range(0..elements.len());
+            let index = rng.random_range(0..elements.len());
             // println!("index: {:?}", index);
             let x = elements.remove(index);
             println!("deleting: {:?}", x);
             tree.delete(x, &mut stats);
             println!("height: {:?}", tree.height());
             println!("{:?}", tree);
+            tree.validate().unwrap();
         }
     }
 
@@ -96,29 +119,31 @@ mod tests {
     fn test_rb_tree_inc() {
         let n = 50usize;
         let mut tree = rb_tree::BinTree::new();
-        let ref mut stats = experiment::Stats::new();
+        let stats = &mut experiment::Stats::new();
         for i in 0..n {
             println!("inserting: {:?}", i);
             tree.insert(i, stats);
             println!("{:?}", tree.height());
             println!("{:?}", tree);
+            tree.validate().unwrap();
         }
         println!("{:?}", tree);
         let mut elements = (0..n).collect::<Vec<_>>();
-        elements.shuffle(&mut rand::thread_rng());
+        elements.shuffle(&mut rand::rng());
 
         for i in elements {
             println!("deleting: {:?}", i);
             tree.delete(i, stats);
             println!("height: {:?}", tree.height());
             println!("{:?}", tree);
+            tree.validate().unwrap();
         }
     }
 
     #[test]
     fn test_splay_tree_rand() {
         let n = 50usize;
-        let mut rng = rand::thread_rng();
+        let mut rng = rand::rng();
         let range = Uniform::new(0, 2 * n - 1).unwrap();
         let mut tree = splay_tree::SplayTree::new();
         let mut elements = Vec::new();
@@ -130,16 +155,18 @@ mod tests {
             elements.push(x);
             println!("height: {:?}", tree.height());
             println!("{:?}", tree);
+            tree.validate().unwrap();
         }
 
         for _i in 0..n {
-            let index = rng.gen_range(0..elements.len());
+            let index = rng.random_range(0..elements.len());
             // println!("index: {:?}", index);
             let x = elements.remove(index);
             println!("deleting: {:?}", x);
             tree.delete(x, &mut stats);
             println!("height: {:?}", tree.height());
             println!("{:?}", tree);
+            tree.validate().unwrap();
         }
     }
 
@@ -147,23 +174,870 @@ mod tests {
     fn test_splay_tree_inc() {
         let n = 50usize;
         let mut tree = splay_tree::SplayTree::new();
-        let ref mut stats = experiment::Stats::new();
+        let stats = &mut experiment::Stats::new();
         for i in 0..n {
             println!("inserting: {:?}", i);
             tree.insert(i, stats);
             println!("{:?}", tree.height());
             println!("{:?}", tree);
+            tree.validate().unwrap();
         }
         println!("{:?}", tree);
         let mut elements = (0..n).collect::<Vec<_>>();
-        elements.shuffle(&mut rand::thread_rng());
+        elements.shuffle(&mut rand::rng());
 
         for i in elements {
             println!("deleting: {:?}", i);
             tree.delete(i, stats);
             println!("height: {:?}", tree.height());
             println!("{:?}", tree);
+            tree.validate().unwrap();
         }
     }
-}
 
+    #[test]
+    fn test_experiment_configs() {
+        let mut names = std::collections::HashSet::new();
+        for entry in std::fs::read_dir("experiments").unwrap() {
+            let path = entry.unwrap().path();
+            let config = config::Config::load(&path);
+            assert!(config.is_ok(), "{}: {}", path.display(), config.unwrap_err());
+            // the name is the results file's, two configs sharing one would write over each other
+            assert!(names.insert(config.unwrap().name), "{}: name already taken", path.display());
+        }
+
+        let broken = toml::from_str::<config::Config>("name = \"x\"\nstructures = [\"avl_tree\"]");
+        assert!(broken.is_err());
+    }
+
+    #[test]
+    fn test_runner_is_deterministic() {
+        let sequence = workload::Workload::Random.generate(200, 7);
+        assert_eq!(sequence, workload::Workload::Random.generate(200, 7));
+
+        let (insert, delete) = runner::run_cell(structure::StructureKind::RbTree, &sequence, runner::Measure { height: true, timing: false });
+        let (insert2, delete2) = runner::run_cell(structure::StructureKind::RbTree, &sequence, runner::Measure { height: true, timing: false });
+        assert_eq!(insert.avg(), insert2.avg());
+        assert_eq!(delete.avg(), delete2.avg());
+    }
+
+    #[test]
+    fn test_results_roundtrip() {
+        let config = config::Config::load(std::path::Path::new("experiments/smoke.json")).unwrap();
+        let outcome = runner::run(&config).unwrap();
+        let saved = results::Saved::new(&config, &outcome);
+
+        let from_csv = results::Saved::from_csv(&saved.to_csv()).unwrap();
+        assert_eq!(from_csv.metadata, saved.metadata);
+        let json = serde_json::to_string(&saved).unwrap();
+        let from_json = serde_json::from_str::<results::Saved>(&json).unwrap();
+        assert_eq!(from_json.metadata, saved.metadata);
+
+        for loaded in [from_csv, from_json] {
+            assert_eq!(loaded.results.len(), saved.results.len());
+            for (a, b) in loaded.results.iter().zip(&saved.results) {
+                assert_eq!(a.structure, b.structure);
+                assert_eq!(a.sizes, b.sizes);
+                assert_eq!(a.insert, b.insert);
+                assert_eq!(a.delete, b.delete);
+            }
+        }
+
+        // an insert row whose delete row is gone leaves the series of different lengths
+        let csv = saved.to_csv();
+        let cut = csv.lines().filter(|line| !line.starts_with("splay_tree,delete,500,")).collect::<Vec<_>>().join("\n");
+        let uneven = results::Saved::from_csv(&cut).unwrap_err();
+        assert!(uneven.to_string().contains("splay_tree has 5 insert rows but 4 delete rows"), "{}", uneven);
+
+        // the same experiment may be written over, a different one may not
+        let path = std::env::temp_dir().join(format!("aisd_results_{}.csv", std::process::id()));
+        saved.save_csv(&path).unwrap();
+        assert!(runner::check_overwrite(&path, &config, &saved).is_ok());
+        let mut other = saved.clone();
+        other.metadata.reps += 1;
+        let refused = runner::check_overwrite(&path, &config, &other).unwrap_err();
+        assert!(refused.to_string().contains("reps"), "{}", refused);
+        other.metadata.reps = saved.metadata.reps;
+        other.results.pop();
+        assert!(runner::check_overwrite(&path, &config, &other).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_reference_structures() {
+        let sequence = workload::Workload::Random.generate(500, 11);
+        for kind in [structure::StructureKind::BTreeSet, structure::StructureKind::RbTreeCrate] {
+            let (insert, delete) = runner::run_cell(kind, &sequence, runner::Measure { height: true, timing: false });
+            let (comps, ptr_read, _, height) = insert.avg();
+            assert!(comps > 0.0, "{} made no comparisons", kind);
+            assert_eq!((ptr_read, height), (0.0, 0.0));
+            assert_eq!(delete.count(), 500);
+        }
+
+        // repeats stay in until deleted as often as they went in, like in our trees
+        use structure::Structure;
+        let mut set = std::collections::BTreeMap::<structure::Counted, usize>::new();
+        let mut stats = experiment::Stats::new();
+        [3, 3, 5].into_iter().for_each(|key| Structure::insert(&mut set, key, &mut stats));
+        Structure::delete(&mut set, 3, &mut stats);
+        assert_eq!(set.get(&structure::Counted(3)), Some(&1));
+        Structure::delete(&mut set, 3, &mut stats);
+        assert_eq!(set.len(), 1);
+    }
+
+    #[test]
+    fn test_timing() {
+        let sequence = workload::Workload::Random.generate(1000, 5);
+        let measure = runner::Measure { height: false, timing: true };
+        let (insert, _) = runner::run_cell(structure::StructureKind::SplayTree, &sequence, measure);
+        assert_eq!(insert.phase_nanos().len(), 1);
+        assert!(insert.avg_nanos() > 0.0);
+        assert!(insert.phase_nanos_per_op() >= insert.avg_nanos());
+
+        let kept = experiment::reject_outliers(&[100, 102, 98, 101, 99, 5000]);
+        assert_eq!(kept.len(), 5);
+    }
+
+    #[test]
+    fn test_checkpoint_resume() {
+        let path = std::env::temp_dir().join(format!("aisd_checkpoint_{}.csv", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let mut config = config::Config::load(std::path::Path::new("experiments/smoke.json")).unwrap();
+        let full = runner::run(&config).unwrap();
+
+        config.outputs.checkpoint = Some(path.clone());
+        runner::run(&config).unwrap();
+        // cut the file in the middle of a row, as an interrupted run would
+        let text = std::fs::read_to_string(&path).unwrap();
+        std::fs::write(&path, &text[..text.len() * 2 / 3]).unwrap();
+
+        let resumed = runner::run(&config).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        for (a, b) in resumed.results.iter().zip(&full.results) {
+            assert_eq!(a.insert, b.insert);
+            assert_eq!(a.delete, b.delete);
+        }
+
+        // a row with a column too many is an error naming its line and both counts
+        runner::run(&config).unwrap();
+        let mut text = std::fs::read_to_string(&path).unwrap();
+        let row = text.lines().position(|line| line.starts_with("bin_tree,")).unwrap();
+        let width = text.lines().nth(row).unwrap().split(',').count();
+        text = text.lines().enumerate().map(|(i, line)| if i == row { format!("{},0\n", line) } else { format!("{}\n", line) }).collect();
+        std::fs::write(&path, text).unwrap();
+        let error = runner::run(&config).err().unwrap().to_string();
+        std::fs::remove_file(&path).unwrap();
+        assert!(error.contains(&format!("line {}: expected {} columns, got {}", row + 1, width, width + 1)), "{}", error);
+    }
+
+    #[test]
+    fn test_parallel_matches_sequential() {
+        let mut config = config::Config::load(std::path::Path::new("experiments/smoke.json")).unwrap();
+        let sequential = runner::run(&config).unwrap();
+        config.threads = 4;
+        let parallel = runner::run(&config).unwrap();
+
+        for (a, b) in parallel.results.iter().zip(&sequential.results) {
+            assert_eq!(a.structure, b.structure);
+            assert_eq!(a.sizes, b.sizes);
+            assert_eq!(a.insert, b.insert);
+            assert_eq!(a.delete, b.delete);
+        }
+    }
+
+    #[test]
+    fn test_fit() {
+        let ns = (1..=10).map(|i| i * 1000).collect::<Vec<_>>();
+        let ys = ns.iter().map(|&n| 3.0 * (n as f64).log2() + 2.0).collect::<Vec<_>>();
+        let best = fit::Fit::best(&ns, &ys).unwrap();
+        assert_eq!(best.model, fit::Model::Log);
+        assert!((best.a - 3.0).abs() < 1e-9 && (best.b - 2.0).abs() < 1e-9);
+        assert!((best.r2 - 1.0).abs() < 1e-9);
+
+        let ys = ns.iter().map(|&n| n as f64 / 2.0).collect::<Vec<_>>();
+        assert_eq!(fit::Fit::best(&ns, &ys).unwrap().model, fit::Model::Linear);
+        assert!(fit::Fit::new(fit::Model::Log, &ns[..1], &ys[..1]).is_none());
+
+        // values that only jump up and down fit no model well enough to name one
+        let ys = ns.iter().map(|&n| (n / 1000 % 2) as f64).collect::<Vec<_>>();
+        assert!(fit::Fit::best(&ns, &ys).unwrap().r2 < fit::MIN_R2);
+        assert!(fit::Fit::good(&ns, &ys).is_none());
+        assert!(fit::Fit::good(&ns, &ns.iter().map(|&n| n as f64).collect::<Vec<_>>()).is_some());
+    }
+
+    #[test]
+    fn test_baseline_check() {
+        use structure::StructureKind;
+        use workload::Workload;
+
+        let runs = [(StructureKind::RbTree, Workload::Random, 200), (StructureKind::SplayTree, Workload::Increasing, 200)];
+        let saved = baseline::Baseline::record(&runs, 7);
+        assert!(saved.compare(&saved.rerun(), 0.0).is_empty());
+
+        let mut shifted = saved.clone();
+        shifted.entries[0].counts.comps += shifted.entries[0].counts.comps / 20;
+        let mismatches = saved.compare(&shifted, 0.01);
+        assert_eq!(mismatches.len(), 1);
+        assert!(matches!(&mismatches[0], baseline::Mismatch::Count { field: "comps", .. }));
+        assert!(saved.compare(&shifted, 0.1).is_empty());
+
+        shifted.entries.pop();
+        assert!(matches!(saved.compare(&shifted, 0.1)[..], [baseline::Mismatch::Missing(_)]));
+    }
+
+    #[test]
+    fn test_splay_potential() {
+        let mut tree = splay_tree::SplayTree::tracked();
+        let stats = &mut experiment::Stats::new();
+        // a path of three nodes: log2(3) + log2(2) + log2(1)
+        for x in [1, 2, 3] {
+            tree.insert(x, stats);
+        }
+        assert!((tree.potential() - 3f64.log2() - 1.0).abs() < 1e-9);
+
+        // amortized costs add up to the rotations plus the final potential
+        let rows = amortized::sweep(workload::Workload::Random, [300].into_iter(), 1, 5);
+        let insert = &rows[0].insert;
+        assert_eq!(insert.count, 300);
+        assert!((insert.amortized - insert.rotations as f64 - insert.potential_change).abs() < 1e-6);
+        assert!(insert.max_amortized <= amortized::bound(300));
+        assert!(rows[0].delete.max_amortized <= amortized::bound(300));
+    }
+
+    #[test]
+    fn test_access_depth() {
+        let keys = [5, 3, 8, 1, 4, 7, 9];
+        let mut bin = bin_tree::BinTree::new();
+        let mut rb = rb_tree::BinTree::new();
+        for x in keys {
+            bin.insert(x, &mut experiment::Stats::new());
+            rb.insert(x, &mut experiment::Stats::new());
+        }
+        // both end up as the same perfect tree
+        for depths in [bin.depths(), rb.depths()] {
+            assert_eq!(depths.counts, vec![1, 2, 4]);
+            assert_eq!(depths.internal_path_length(), 10);
+        }
+
+        let mut data = experiment::Data::new();
+        for x in [7, 3, 5, 6] {
+            let stats = &mut experiment::Stats::new();
+            bin.delete(x, stats);
+            data.add_stat(stats.clone());
+        }
+        // 7 and 3 at depth 2 and 1, 5 at the root, 6 missed below 4 at depth 1
+        assert_eq!(data.avg_depth(), 1.0);
+        assert_eq!(experiment::Metric::MaxDepth.value(&data), 2.0);
+
+        let mut splay = splay_tree::SplayTree::new();
+        let stats = &mut experiment::Stats::new();
+        for x in [1, 2, 3] {
+            splay.insert(x, stats);
+        }
+        // inserted keys are splayed up, so 3 is the root of the path 3-2-1
+        assert_eq!(splay.depths().counts, vec![1, 1, 1]);
+        let mut data = experiment::Data::new();
+        let stats = &mut experiment::Stats::new();
+        splay.delete(1, stats);
+        data.add_stat(stats.clone());
+        assert_eq!(experiment::Metric::MaxDepth.value(&data), 2.0);
+    }
+
+    #[test]
+    fn test_shape() {
+        let stats = &mut experiment::Stats::new();
+        let mut rb = rb_tree::BinTree::new();
+        let mut bin = bin_tree::BinTree::new();
+        for x in 0..100 {
+            rb.insert(x, stats);
+            bin.insert(x, stats);
+        }
+
+        let shape = rb.shape();
+        assert_eq!(shape.size, 100);
+        assert_eq!(shape.height, rb.height());
+        assert_eq!(shape.external_path_length, shape.internal_path_length() + 2 * shape.size);
+        assert!(shape.black_height.is_some());
+        assert!(shape.height_ratio() <= 2.0);
+        assert_eq!(shape.balance_factors.values().sum::<usize>(), 100);
+
+        // sorted inserts leave a path leaning right
+        let shape = bin.shape();
+        assert_eq!((shape.height, shape.min_leaf_depth), (100, 99));
+        assert_eq!(shape.balance_factors[&99], 1);
+        assert_eq!(shape.black_height, None);
+        assert_eq!(splay_tree::SplayTree::<usize>::new().shape(), shape::Shape::default());
+    }
+
+    #[test]
+    fn test_chart_options() {
+        let dir = std::env::temp_dir().join(format!("aisd_charts_{}", std::process::id()));
+        let options = chart::ChartOptions::new().dir(&dir).format(chart::Format::Svg).size(400, 300)
+            .title("comparisons").x_label("n").y_label("comps");
+
+        let data = vec![vec![1.0, 4.0, 9.0], vec![2.0, 2.0, 2.0]];
+        let file = chart::draw_chart(data, vec!["square", "flat"], [1, 2, 3].into_iter(), "test", &options, |_, y| y).unwrap().unwrap();
+        assert_eq!(file, dir.join("chart_test.svg"));
+        assert!(std::fs::read_to_string(&file).unwrap().contains("<svg"));
+
+        let empty = chart::draw_chart::<f64>(vec![], vec![], 1..1, "empty", &options, |_, y| y);
+        assert!(matches!(empty, Err(chart::ChartError::Empty(_))));
+        let nan = chart::draw_chart(vec![vec![f64::NAN]], vec!["nan"], [1].into_iter(), "nan", &options, |_, y| y);
+        assert!(matches!(nan, Err(chart::ChartError::Empty(_))));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_chart_scales() {
+        assert_eq!(chart::Normalize::Log2N.apply(1024.0, 20.0), 2.0);
+        assert_eq!("ln_n".parse::<chart::Normalize>(), Ok(chart::Normalize::LnN));
+        assert!("log n".parse::<chart::Normalize>().is_err());
+
+        let (means, band) = chart::Band::stddev(&[vec![1.0, 3.0], vec![5.0, 5.0, 5.0]]);
+        assert_eq!(means, vec![2.0, 5.0]);
+        assert!((band.high[0] - 2.0 - 2.0f64.sqrt()).abs() < 1e-9);
+        assert_eq!((band.low[1], band.high[1]), (5.0, 5.0));
+
+        let dir = std::env::temp_dir().join(format!("aisd_scales_{}", std::process::id()));
+        let options = chart::ChartOptions::new().dir(&dir).format(chart::Format::Svg).size(400, 300)
+            .log_x(true).log_y(true).normalize(Some(chart::Normalize::Log2N));
+        let series = vec![chart::Series {
+            name: "comps",
+            values: vec![0.0, 20.0, 30.0],
+            band: Some(chart::Band { low: vec![0.0, 20.0, 30.0], high: vec![1.0, 40.0, 60.0] }),
+        }];
+        let file = chart::draw_series(series, [1, 1024, 4096].into_iter(), "scales", &options).unwrap().unwrap();
+        assert!(std::fs::read_to_string(&file).unwrap().contains("<polygon"));
+
+        // only zeros, nothing left to draw on a log axis
+        let zeros = chart::draw_chart(vec![vec![0.0, 0.0]], vec!["zero"], [1, 2].into_iter(), "zeros", &options, |_, y| y);
+        assert!(matches!(zeros, Err(chart::ChartError::Empty(_))));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_terminal_chart() {
+        let options = chart::ChartOptions::new().format(chart::Format::Terminal).text_size(40, 8)
+            .title("comps").x_label("n").y_label("avg comps");
+        let series = |name, values| chart::Series { name, values, band: None };
+        let lines = vec![series("rising", vec![0.0, 5.0, 10.0]), series("flat", vec![5.0, 5.0, 5.0])];
+        let text = chart::render_text(lines, [100, 200, 300].into_iter(), "comps", &options, false).unwrap();
+
+        let rows = text.lines().collect::<Vec<_>>();
+        assert_eq!(rows[1], "avg comps");
+        assert!(rows[2].starts_with("11.00 ┤"));
+        assert!(rows.iter().any(|row| row.contains("100.00") && row.contains("300.00")));
+        assert!(rows.last().unwrap().contains("● rising  ▲ flat"));
+        // each point carries its series' marker, at n = 200 both lines meet and the later one shows
+        assert_eq!(text.matches('●').count(), 3);
+        assert_eq!(text.matches('▲').count(), 4);
+        // the rising line starts in the bottom left corner and ends in the top right one
+        let plot = &rows[2..10];
+        assert!(plot[7].chars().nth(7).is_some_and(|c| c != '⠀'));
+        assert!(plot[0].chars().last().is_some_and(|c| c != '⠀'));
+        assert!(!text.contains('\x1b'));
+
+        // printed instead of written, there is no file to return
+        let printed = chart::draw_chart(vec![vec![1.0, 2.0]], vec!["x"], [1, 2].into_iter(), "printed", &options, |_, y| y);
+        assert!(matches!(printed, Ok(None)));
+    }
+
+    #[test]
+    fn test_dot() {
+        let stats = &mut experiment::Stats::new();
+        let mut rb = rb_tree::BinTree::new();
+        (0..20).for_each(|x| rb.insert(x, stats));
+        let options = dot::DotOptions::new().null_leaves(true).parents(true).annotate(dot::Annotation::Depth);
+        let graph = rb.to_dot(&options);
+        assert!(graph.starts_with("digraph tree {") && graph.ends_with("}\n"));
+        assert_eq!(graph.matches("label=\"NIL\"").count(), 21);
+        assert_eq!(graph.matches("\\ndepth").count(), 20);
+        assert!(graph.contains("fillcolor=red"));
+        // every parent pointer checks out, the root's points back at itself and gets no edge
+        assert_eq!(graph.matches("color=blue").count(), 19);
+        assert!(!graph.contains("color=red, constraint=false"));
+        assert!(graph.contains("label=\"7\\ndepth 0\""));
+
+        let mut bin = bin_tree::BinTree::new();
+        [2, 1, 3, 4].into_iter().for_each(|x| bin.insert(x, stats));
+        let graph = bin.to_dot(&dot::DotOptions::new());
+        // 3 has only a right child, a hidden left one keeps it on the right
+        assert!(graph.contains("nil2l [shape=point, style=invis];"));
+        assert!(!graph.contains("fillcolor=red"));
+
+        let mut splay = splay_tree::SplayTree::new();
+        [2, 1, 3].into_iter().for_each(|x| splay.insert(x, stats));
+        let graph = splay.to_dot(&dot::DotOptions::new().parents(true).annotate(dot::Annotation::Size));
+        assert!(graph.contains("n0 [label=\"3\\nsize 3\"];"));
+        assert!(!graph.contains("dashed"));
+    }
+
+    #[test]
+    fn test_validate() {
+        use dot::DotNode;
+        use shape::NodeView;
+        use validate::{validate, CheckedNode, InvariantError};
+
+        // a hand-made colored tree of (key, red, left, right), the root at 0
+        #[derive(Clone, Copy)]
+        struct Fixed(&'static [(usize, bool, Option<usize>, Option<usize>)], usize);
+        impl NodeView for Fixed {
+            fn left(&self) -> Option<Self> {
+                self.0[self.1].2.map(|i| Fixed(self.0, i))
+            }
+            fn right(&self) -> Option<Self> {
+                self.0[self.1].3.map(|i| Fixed(self.0, i))
+            }
+            fn red(&self) -> Option<bool> {
+                Some(self.0[self.1].1)
+            }
+            fn id(&self) -> usize {
+                self.1
+            }
+        }
+        impl DotNode for Fixed {
+            fn key(&self) -> String {
+                self.0[self.1].0.to_string()
+            }
+        }
+        impl CheckedNode for Fixed {
+            fn cmp_key(&self, other: &Self) -> std::cmp::Ordering {
+                self.0[self.1].0.cmp(&other.0[other.1].0)
+            }
+        }
+
+        let fine = Fixed(&[(2, false, Some(1), Some(2)), (1, true, None, None), (3, true, None, None)], 0);
+        assert_eq!(validate(Some(fine), Some(3), None), Ok(()));
+        assert_eq!(validate(Some(fine), Some(4), None), Err(InvariantError::Size { recorded: 4, counted: 3 }));
+        assert_eq!(validate(Some(fine), None, Some(2)), Err(InvariantError::StrayNil { key: "3".to_string() }));
+        let unordered = Fixed(&[(2, false, Some(1), None), (3, true, None, None)], 0);
+        assert_eq!(validate(Some(unordered), None, None), Err(InvariantError::Order { key: "2".to_string(), previous: "3".to_string() }));
+        let red_red = Fixed(&[(3, false, Some(1), Some(3)), (2, true, Some(2), None), (1, true, None, None), (4, true, None, None)], 0);
+        assert_eq!(validate(Some(red_red), None, None), Err(InvariantError::RedRed { key: "1".to_string(), parent: "2".to_string() }));
+        let uneven = Fixed(&[(2, false, Some(1), None), (1, false, None, None)], 0);
+        let error = validate(Some(uneven), None, None).unwrap_err();
+        assert_eq!(error.to_string(), "black height under 2 is 1 on the left and 0 on the right");
+        let looped = Fixed(&[(2, false, Some(0), None)], 0);
+        assert_eq!(validate(Some(looped), None, None), Err(InvariantError::Revisited { key: "2".to_string() }));
+
+        // duplicates and deletes of missing keys on the real trees
+        let stats = &mut experiment::Stats::new();
+        let mut bin = bin_tree::BinTree::new();
+        let mut rb = rb_tree::BinTree::new();
+        let mut splay = splay_tree::SplayTree::new();
+        let keys = [5, 3, 5, 8, 3, 1, 9, 5];
+        for x in keys {
+            bin.insert(x, stats);
+            rb.insert(x, stats);
+            splay.insert(x, stats);
+        }
+        for x in [5, 4, 3, 9, 9] {
+            bin.delete(x, stats);
+            rb.delete(x, stats);
+            splay.delete(x, stats);
+            bin.validate().unwrap();
+            rb.validate().unwrap();
+            splay.validate().unwrap();
+        }
+    }
+
+    #[test]
+    fn test_differential() {
+        use differential::{check, random_ops};
+        // every step checks the whole tree, which is slow in debug builds
+        for seed in 0..6 {
+            let ops = random_ops(seed, 1_500, 48);
+            let results = [
+                check::<bin_tree::BinTree<usize>>(&ops),
+                check::<rb_tree::BinTree<usize>>(&ops),
+                check::<splay_tree::SplayTree<usize>>(&ops),
+            ];
+            for result in results {
+                if let Err(failure) = result {
+                    panic!("seed {}: {}", seed, failure);
+                }
+            }
+        }
+
+        // forgets every delete of 7, the shrinker has to get down to that one op
+        #[derive(Default)]
+        struct Forgetful(splay_tree::SplayTree<usize>);
+        impl differential::Subject for Forgetful {
+            const NAME: &'static str = "forgetful";
+            fn insert(&mut self, key: usize) {
+                self.0.insert(key, &mut experiment::Stats::new());
+            }
+            fn delete(&mut self, key: usize) -> bool {
+                key == 7 || self.0.delete(key, &mut experiment::Stats::new())
+            }
+            fn contains(&self, key: usize) -> bool {
+                self.0.contains(&key)
+            }
+            fn keys(&self) -> Vec<usize> {
+                self.0.iter().copied().collect()
+            }
+            fn validate(&self) -> Result<(), String> {
+                Ok(())
+            }
+        }
+        let failure = check::<Forgetful>(&random_ops(0, 500, 16)).unwrap_err();
+        assert_eq!(failure.original, 500);
+        assert_eq!(failure.ops, vec![differential::Op::Delete(7)]);
+        assert!(failure.to_string().ends_with("step 0 (delete 7): delete returned true, expected false"));
+    }
+
+    #[test]
+    fn test_fuzz_decode() {
+        use differential::{decode, fuzz_one, Op};
+        assert_eq!(decode(&[0, 5, 8, 5, 15, 5, 19, 5, 20, 255, 7]), vec![
+            Op::Insert(5), Op::Delete(5), Op::Contains(5), Op::Iterate, Op::Insert(255),
+        ]);
+        // the corpus a fuzzer starts from has to pass
+        for data in [&[][..], &[0, 1, 0, 1, 8, 1, 15, 1, 8, 1, 8, 1]] {
+            fuzz_one::<bin_tree::BinTree<usize>>(data);
+            fuzz_one::<rb_tree::BinTree<usize>>(data);
+            fuzz_one::<splay_tree::SplayTree<usize>>(data);
+        }
+    }
+
+    #[test]
+    fn test_live_nodes() {
+        use live::live_nodes;
+        use structure::StructureKind;
+        let stats = &mut experiment::Stats::new();
+        let ops = differential::random_ops(3, 2_000, 64);
+        let keys = || ops.iter().filter_map(|op| match op {
+            differential::Op::Insert(key) => Some(*key),
+            _ => None,
+        });
+
+        let mut bin = bin_tree::BinTree::new();
+        let mut rb = rb_tree::BinTree::new();
+        let mut splay = splay_tree::SplayTree::new();
+        for op in &ops {
+            match *op {
+                differential::Op::Insert(key) => {
+                    bin.insert(key, stats);
+                    rb.insert(key, stats);
+                    splay.insert(key, stats);
+                }
+                differential::Op::Delete(key) => {
+                    bin.delete(key, stats);
+                    rb.delete(key, stats);
+                    splay.delete(key, stats);
+                }
+                _ => {}
+            }
+            // a removed node that is still around is a leak, the rb stand-in for nil included
+            assert_eq!(live_nodes(StructureKind::BinTree), bin.len());
+            assert_eq!(live_nodes(StructureKind::RbTree), rb.len());
+            assert_eq!(live_nodes(StructureKind::SplayTree), splay.iter().count());
+        }
+        assert!(!bin.is_empty() && !rb.is_empty() && !splay.is_empty());
+        bin.clear();
+        rb.clear();
+        splay.clear();
+        assert!(bin.is_empty() && rb.is_empty() && splay.is_empty());
+        assert_eq!(rb.len(), 0);
+        for kind in StructureKind::TREES {
+            assert_eq!(live_nodes(kind), 0, "{}", kind);
+        }
+
+        // dropping has to free everything too, the parent links are weak
+        let mut rb = rb_tree::BinTree::new();
+        keys().for_each(|key| rb.insert(key, stats));
+        let mut bin = bin_tree::BinTree::new();
+        keys().for_each(|key| bin.insert(key, stats));
+        assert_eq!(live_nodes(StructureKind::RbTree), rb.len());
+        drop(rb);
+        drop(bin);
+        assert_eq!(live_nodes(StructureKind::RbTree), 0);
+        assert_eq!(live_nodes(StructureKind::BinTree), 0);
+
+        // a cleared tree is as good as new
+        splay.insert(1, stats);
+        assert_eq!(splay.iter().collect::<Vec<_>>(), vec![&1]);
+    }
+
+    #[test]
+    fn test_from_sorted_iter() {
+        for n in 0..70usize {
+            // repeats included, every key twice
+            let keys = (0..n).map(|i| i / 2).collect::<Vec<_>>();
+            let height = (n + 1).next_power_of_two().ilog2() as usize;
+            let bin = bin_tree::BinTree::from_sorted_iter(keys.clone());
+            let rb = rb_tree::BinTree::from_sorted_iter(keys.clone());
+            let splay = splay_tree::SplayTree::from_sorted_iter(keys.clone());
+            bin.validate().unwrap();
+            rb.validate().unwrap();
+            splay.validate().unwrap();
+            assert_eq!(bin.iter().collect::<Vec<_>>(), keys);
+            assert_eq!(rb.iter().collect::<Vec<_>>(), keys);
+            assert_eq!(splay.iter().copied().collect::<Vec<_>>(), keys);
+            assert_eq!((bin.len(), rb.len()), (n, n));
+            assert_eq!((bin.height(), rb.height(), splay.height() as usize), (height, height, height));
+        }
+        let unsorted = std::panic::catch_unwind(|| rb_tree::BinTree::from_sorted_iter([2, 1]));
+        assert!(unsorted.is_err());
+
+        // sorted input takes the bulk path, a degenerate bin_tree would be 200000 deep
+        let big = (0..200_000).collect::<bin_tree::BinTree<usize>>();
+        assert_eq!(big.height(), 18);
+        drop(big);
+
+        // merged into what is there, or inserted one at a time when not sorted
+        let stats = &mut experiment::Stats::new();
+        let mut rb = [5, 1, 9].into_iter().collect::<rb_tree::BinTree<usize>>();
+        rb.extend([0, 2, 5, 7, 8, 10]);
+        rb.validate().unwrap();
+        assert_eq!(rb.iter().collect::<Vec<_>>(), vec![0, 1, 2, 5, 5, 7, 8, 9, 10]);
+        rb.extend([4, 3]);
+        rb.delete(5, stats);
+        rb.validate().unwrap();
+        assert_eq!(rb.iter().collect::<Vec<_>>(), vec![0, 1, 2, 3, 4, 5, 7, 8, 9, 10]);
+
+        let mut splay = splay_tree::SplayTree::tracked();
+        splay.insert(3, stats);
+        splay.extend([1, 2, 3, 4]);
+        assert_eq!(splay.iter().copied().collect::<Vec<_>>(), vec![1, 2, 3, 3, 4]);
+        splay.insert(0, stats);
+        assert!(splay.last_step().is_some());
+
+        let mut bin = [3, 1, 2].into_iter().collect::<bin_tree::BinTree<usize>>();
+        bin.extend([0]);
+        bin.validate().unwrap();
+        assert_eq!((bin.len(), bin.height()), (4, 3));
+        drop((bin, rb, splay));
+        for kind in structure::StructureKind::TREES {
+            assert_eq!(live::live_nodes(kind), 0, "{}", kind);
+        }
+    }
+
+    #[test]
+    fn test_split_join() {
+        use rand::SeedableRng;
+        let rng = &mut rand_pcg::Pcg64::seed_from_u64(11);
+        let stats = &mut experiment::Stats::new();
+        for n in [0, 1, 2, 3, 10, 57, 300] {
+            let keys = (0..n).map(|_| rng.random_range(0..n / 2 + 1)).collect::<Vec<usize>>();
+            let mut sorted = keys.clone();
+            sorted.sort();
+            for key in [0, n / 4, n / 3, n / 2, n / 2 + 1] {
+                let (below, above) = sorted.iter().partition::<Vec<usize>, _>(|&&k| k < key);
+
+                let mut rb = rb_tree::BinTree::new();
+                keys.iter().for_each(|&k| rb.insert(k, stats));
+                let upper = rb.split_off(&key);
+                rb.validate().unwrap();
+                upper.validate().unwrap();
+                assert_eq!(rb.iter().collect::<Vec<_>>(), below);
+                assert_eq!(upper.iter().collect::<Vec<_>>(), above);
+                let rb = rb_tree::BinTree::join(rb, upper);
+                rb.validate().unwrap();
+                assert_eq!(rb.iter().collect::<Vec<_>>(), sorted);
+
+                let mut splay = splay_tree::SplayTree::new();
+                keys.iter().for_each(|&k| splay.insert(k, stats));
+                let upper = splay.split_off(&key);
+                splay.validate().unwrap();
+                upper.validate().unwrap();
+                assert_eq!(splay.iter().copied().collect::<Vec<_>>(), below);
+                assert_eq!(upper.iter().copied().collect::<Vec<_>>(), above);
+                let splay = splay_tree::SplayTree::join(splay, upper);
+                splay.validate().unwrap();
+                assert_eq!(splay.iter().copied().collect::<Vec<_>>(), sorted);
+            }
+        }
+
+        // black heights far apart, the short tree on either side
+        let tall = || rb_tree::BinTree::from_sorted_iter(10..1_000);
+        let mut short = rb_tree::BinTree::new();
+        short.insert(5, stats);
+        let mut rb = rb_tree::BinTree::join(short, tall());
+        rb.validate().unwrap();
+        rb.append(&mut rb_tree::BinTree::from_sorted_iter([1_000, 1_001]));
+        rb.validate().unwrap();
+        assert_eq!((rb.len(), rb.iter().next(), rb.iter().last()), (993, Some(5), Some(1_001)));
+        let mut high = rb.split_off(&999);
+        assert_eq!((rb.len(), high.len()), (990, 3));
+        high.append(&mut rb);
+        high.validate().unwrap();
+        assert!(rb.is_empty());
+        assert_eq!(high.iter().collect::<Vec<_>>(), std::iter::once(5).chain(10..1_002).collect::<Vec<_>>());
+        // overlapping keys are merged instead
+        high.append(&mut tall());
+        high.validate().unwrap();
+        assert_eq!(high.len(), 993 + 990);
+
+        let mut splay = splay_tree::SplayTree::tracked();
+        splay.insert(5, stats);
+        splay.append(&mut splay_tree::SplayTree::from_sorted_iter(0..5));
+        splay.append(&mut splay_tree::SplayTree::from_sorted_iter(3..8));
+        splay.append(&mut splay_tree::SplayTree::from_sorted_iter([7, 8]));
+        splay.append(&mut splay_tree::SplayTree::new());
+        splay.validate().unwrap();
+        assert_eq!(splay.iter().copied().collect::<Vec<_>>(), vec![0, 1, 2, 3, 3, 4, 4, 5, 5, 6, 7, 7, 8]);
+        let mut empty = splay_tree::SplayTree::tracked();
+        empty.append(&mut splay);
+        assert!(splay.is_empty());
+        let mut splay = empty;
+        splay.insert(9, stats);
+        assert!(splay.last_step().is_some());
+
+        let overlap = std::panic::catch_unwind(|| {
+            rb_tree::BinTree::join(rb_tree::BinTree::from_sorted_iter([2, 3]), rb_tree::BinTree::from_sorted_iter([1]))
+        });
+        assert!(overlap.is_err());
+        let overlap = std::panic::catch_unwind(|| {
+            splay_tree::SplayTree::join(splay_tree::SplayTree::from_sorted_iter([2, 3]), splay_tree::SplayTree::from_sorted_iter([1, 4]))
+        });
+        assert!(overlap.is_err());
+        drop((high, splay));
+        for kind in structure::StructureKind::TREES {
+            assert_eq!(live::live_nodes(kind), 0, "{}", kind);
+        }
+    }
+
+    // the fuzz targets on random bytes, for when cargo fuzz isn't around:
+    // cargo test --release -- --ignored fuzz
+    fn fuzz_random<S: differential::Subject>(runs: u64, len: usize, keys: u8, delete_heavy: bool) {
+        let rng = &mut <rand_pcg::Pcg64 as rand::SeedableRng>::seed_from_u64(S::NAME.len() as u64);
+        for run in 0..runs {
+            let mut data = vec![0u8; 2 * len];
+            rng.fill(&mut data[..]);
+            for (i, pair) in data.chunks_exact_mut(2).enumerate() {
+                pair[1] %= keys;
+                // fill up for the first half, then mostly deletes until it drains
+                if delete_heavy {
+                    pair[0] = if i < len / 2 { pair[0] % 8 } else { 8 + pair[0] % 12 };
+                }
+            }
+            if let Err(failure) = differential::check::<S>(&differential::decode(&data)) {
+                panic!("run {}: {}", run, failure);
+            }
+        }
+    }
+
+    #[test]
+    #[ignore = "long-running, run with --release"]
+    fn fuzz_bin_tree() {
+        fuzz_random::<bin_tree::BinTree<usize>>(2_000, 400, 64, false);
+    }
+
+    #[test]
+    #[ignore = "long-running, run with --release"]
+    fn fuzz_rb_tree() {
+        fuzz_random::<rb_tree::BinTree<usize>>(2_000, 400, 64, false);
+        // the delete fixups only get going on larger trees emptied out again
+        fuzz_random::<rb_tree::BinTree<usize>>(500, 1_000, 255, true);
+    }
+
+    #[test]
+    #[ignore = "long-running, run with --release"]
+    fn fuzz_splay_tree() {
+        fuzz_random::<splay_tree::SplayTree<usize>>(2_000, 400, 64, false);
+    }
+
+    #[test]
+    fn test_printer() {
+        use printer::{Layout, PrintOptions, RedStyle};
+        let stats = &mut experiment::Stats::new();
+        let mut rb = rb_tree::BinTree::new();
+        [1, 2, 3].into_iter().for_each(|x| rb.insert(x, stats));
+        // no escapes unless asked for
+        assert_eq!(format!("{:?}", rb), "   ╭──[3]\n   2\n   ╰──[1]\n");
+        let mut out = Vec::new();
+        rb.print(&mut out, &PrintOptions::new().red(RedStyle::Ansi).layout(Layout::Vertical)).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "  2\n┌─┴─┐\n\x1b[31m1\x1b[0m   \x1b[31m3\x1b[0m\n");
+
+        let mut bin = bin_tree::BinTree::new();
+        [2, 1, 3, 4].into_iter().for_each(|x| bin.insert(x, stats));
+        let mut out = Vec::new();
+        bin.print(&mut out, &PrintOptions::new().layout(Layout::Vertical)).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "  2\n┌─┴─┐\n1   3\n    └─┐\n      4\n");
+        let mut out = Vec::new();
+        bin.print(&mut out, &PrintOptions::new().max_depth(Some(1))).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "      ╭──…\n   ╭──3\n   2\n   ╰──1\n");
+        let mut out = Vec::new();
+        bin.print(&mut out, &PrintOptions::new().max_nodes(Some(2))).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "   ╭──…\n   2\n   ╰──1\n");
+
+        // the writer's error comes back instead of a panic
+        struct Closed;
+        impl std::io::Write for Closed {
+            fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+                Err(std::io::ErrorKind::BrokenPipe.into())
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+        let splay = splay_tree::SplayTree::<usize>::new();
+        assert_eq!(format!("{:?}", splay), "Empty tree\n");
+        let error = bin.print(&mut Closed, &PrintOptions::new()).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::BrokenPipe);
+    }
+
+    #[test]
+    fn test_frames() {
+        let frames = frames::animate(structure::StructureKind::RbTree, &[1, 2, 3], &[]).unwrap();
+        let captions = frames.iter().map(|frame| frame.caption.as_str()).collect::<Vec<_>>();
+        assert_eq!(captions[0], "insert 1");
+        assert!(captions.contains(&"insert case 3: uncle black, recolor and left-rotate grandparent"));
+        let mut rb = rb_tree::BinTree::new();
+        [1, 2, 3].into_iter().for_each(|x| rb.insert(x, &mut experiment::Stats::new()));
+        assert_eq!(frames.last().unwrap().tree, rb.snapshot());
+
+        // the splay tree works on detached subtrees, every frame must still hold all of it
+        let keys = [5, 3, 8, 1, 4, 2, 7, 6];
+        let frames = frames::animate(structure::StructureKind::SplayTree, &keys, &[3, 8]).unwrap();
+        assert!(frames.iter().any(|frame| frame.caption.contains("zig-zig")));
+        assert!(frames.iter().any(|frame| frame.caption.contains("zig-zag")));
+        // sizes before and after the operation the frame belongs to
+        let mut sizes = (0, 0);
+        for frame in &frames {
+            match frame.caption.split_once(' ') {
+                Some(("insert", key)) if key.parse::<usize>().is_ok() => sizes = (sizes.1, sizes.1 + 1),
+                Some(("delete", key)) if key.parse::<usize>().is_ok() => sizes = (sizes.1, sizes.1 - 1),
+                _ => assert!(frame.tree.len() == sizes.0 || frame.tree.len() == sizes.1, "{}", frame.caption),
+            }
+        }
+        assert_eq!(frames.last().unwrap().tree.len(), keys.len() - 2);
+
+        // nothing is kept unless asked for
+        let mut stats = experiment::Stats::new();
+        let mut splay = splay_tree::SplayTree::new();
+        keys.into_iter().for_each(|x| splay.insert(x, &mut stats));
+        assert!(!stats.is_recording() && stats.take_frames().is_empty());
+        assert!(frames::animate(structure::StructureKind::BTreeSet, &keys, &[]).is_none());
+
+        // keys that cannot be printed still go in and out, only the framed operations need Debug
+        #[derive(PartialEq, Eq, PartialOrd, Ord, Clone)]
+        struct Opaque(usize);
+        let mut bin = bin_tree::BinTree::from_iter(keys.map(Opaque));
+        assert!(bin.delete(Opaque(3), &mut stats) && !bin.contains(&Opaque(3)));
+        let mut splay = splay_tree::SplayTree::from_iter(keys.map(Opaque));
+        assert!(splay.delete(Opaque(3), &mut stats) && !splay.contains(&Opaque(3)));
+    }
+
+    #[test]
+    fn test_report() {
+        let config = config::Config::load(std::path::Path::new("experiments/smoke.json")).unwrap();
+        let saved = results::Saved::new(&config, &runner::run(&config).unwrap());
+        let dir = std::env::temp_dir().join(format!("aisd_report_{}", std::process::id()));
+        let options = chart::ChartOptions::new().dir(&dir).format(chart::Format::Svg).size(300, 200);
+
+        let metrics = [experiment::Metric::AvgComps, experiment::Metric::MaxHeight, experiment::Metric::PhaseNanos];
+        let index = report::write_report(&saved, &metrics, &options).unwrap();
+        // named like the charts, reports of other experiments in the same directory keep theirs
+        assert_eq!(index, dir.join("smoke_index.md"));
+        let page = std::fs::read_to_string(&index).unwrap();
+        assert!(page.contains("![insert report](chart_smoke_insert_report.svg)"));
+        assert!(page.contains("| rb_tree | 500 |"));
+        // never timed, so there is no chart to link
+        assert!(page.contains("[avg comps](chart_smoke_delete_avg_comps.svg)"));
+        assert!(!page.contains("[phase ns/op]"));
+        assert!(dir.join("chart_smoke_delete_report.svg").exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,86 @@
+use crate::structure::StructureKind;
+
+// every node carries one of these, it counts itself in when made and out when dropped;
+// a tree that is gone but left nodes behind is leaking them, through an Rc cycle or
+// a strong reference kept somewhere it shouldn't be
+//
+// the counting is only compiled in for the tests and with the count-nodes feature,
+// otherwise Live takes no room and does nothing
+#[cfg(any(test, feature = "count-nodes"))]
+#[derive(Debug)]
+pub(crate) struct Live(StructureKind);
+
+#[cfg(not(any(test, feature = "count-nodes")))]
+#[derive(Debug)]
+pub(crate) struct Live;
+
+#[cfg(any(test, feature = "count-nodes"))]
+mod counting {
+    use std::cell::Cell;
+
+    use super::Live;
+    use crate::structure::StructureKind;
+
+    // per thread, the trees are Rc based and stay on the thread that made them,
+    // so tests running side by side don't see each other's nodes
+    thread_local! {
+        static LIVE: [Cell<usize>; 3] = const { [Cell::new(0), Cell::new(0), Cell::new(0)] };
+    }
+
+    fn slot(kind: StructureKind) -> usize {
+        match kind {
+            StructureKind::BinTree => 0,
+            StructureKind::RbTree => 1,
+            StructureKind::SplayTree => 2,
+            _ => panic!("{} has no nodes of ours", kind),
+        }
+    }
+
+    fn add(kind: StructureKind, delta: isize) {
+        LIVE.with(|live| {
+            let count = &live[slot(kind)];
+            count.set(count.get().checked_add_signed(delta).expect("more nodes dropped than made"));
+        });
+    }
+
+    impl Live {
+        pub(crate) fn new(kind: StructureKind) -> Self {
+            add(kind, 1);
+            Live(kind)
+        }
+    }
+
+    impl Clone for Live {
+        fn clone(&self) -> Self {
+            Live::new(self.0)
+        }
+    }
+
+    impl Drop for Live {
+        fn drop(&mut self) {
+            add(self.0, -1);
+        }
+    }
+
+    // nodes of the kind made on this thread and not dropped yet
+    pub fn live_nodes(kind: StructureKind) -> usize {
+        LIVE.with(|live| live[slot(kind)].get())
+    }
+}
+
+#[cfg(any(test, feature = "count-nodes"))]
+pub use counting::live_nodes;
+
+#[cfg(not(any(test, feature = "count-nodes")))]
+impl Live {
+    pub(crate) fn new(_kind: StructureKind) -> Self {
+        Live
+    }
+}
+
+#[cfg(not(any(test, feature = "count-nodes")))]
+impl Clone for Live {
+    fn clone(&self) -> Self {
+        Live
+    }
+}
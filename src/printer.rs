@@ -0,0 +1,291 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::io;
+use std::str::FromStr;
+
+use crate::dot::DotNode;
+
+// how red nodes stand out from black ones
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RedStyle {
+    // red ansi escapes, for terminals
+    Ansi,
+    // [key], survives logs and snapshots
+    #[default]
+    Brackets,
+    // no difference at all
+    Plain,
+}
+
+impl RedStyle {
+    pub const ALL: [RedStyle; 3] = [RedStyle::Ansi, RedStyle::Brackets, RedStyle::Plain];
+
+    pub fn key(&self) -> &'static str {
+        match self {
+            RedStyle::Ansi => "ansi",
+            RedStyle::Brackets => "brackets",
+            RedStyle::Plain => "plain",
+        }
+    }
+}
+
+impl FromStr for RedStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        RedStyle::ALL.into_iter()
+            .find(|style| style.key() == s)
+            .ok_or_else(|| format!("unknown red style `{}`", s))
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Layout {
+    // root on the left, right subtree on top
+    #[default]
+    Sideways,
+    // root on top, children below
+    Vertical,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct PrintOptions {
+    red: RedStyle,
+    layout: Layout,
+    max_depth: Option<usize>,
+    max_nodes: Option<usize>,
+}
+
+impl PrintOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn red(mut self, red: RedStyle) -> Self {
+        self.red = red;
+        self
+    }
+
+    pub fn layout(mut self, layout: Layout) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    // deepest level printed, the root being at depth 0; what hangs below is drawn as …
+    pub fn max_depth(mut self, max_depth: Option<usize>) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    // the first nodes in level order are printed, the subtrees left out are drawn as …
+    pub fn max_nodes(mut self, max_nodes: Option<usize>) -> Self {
+        self.max_nodes = max_nodes;
+        self
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Child {
+    None,
+    Node(usize),
+    Elided,
+}
+
+struct Entry {
+    label: String,
+    // printed characters, the escapes around a red label take no room
+    width: usize,
+    depth: usize,
+    left: Child,
+    right: Child,
+}
+
+const ELLIPSIS: &str = "…";
+
+// the part of the tree within the limits, in level order so entries[0] is the root
+fn collect<N: DotNode>(root: N, options: &PrintOptions) -> Vec<Entry> {
+    let mut entries = Vec::new();
+    let mut queue = VecDeque::from([(root, 0)]);
+    while let Some((node, depth)) = queue.pop_front() {
+        let key = node.key();
+        let width = key.chars().count();
+        let (label, width) = match (node.red(), options.red) {
+            (Some(true), RedStyle::Ansi) => (format!("\x1b[31m{}\x1b[0m", key), width),
+            (Some(true), RedStyle::Brackets) => (format!("[{}]", key), width + 2),
+            _ => (key, width),
+        };
+        entries.push(Entry { label, width, depth, left: Child::None, right: Child::None });
+        let index = entries.len() - 1;
+        for (is_left, child) in [(true, node.left()), (false, node.right())] {
+            let Some(child) = child else { continue };
+            let shown = entries.len() + queue.len();
+            let child = if options.max_depth.is_some_and(|max| depth + 1 > max)
+                || options.max_nodes.is_some_and(|max| shown >= max) {
+                Child::Elided
+            } else {
+                queue.push_back((child, depth + 1));
+                Child::Node(shown)
+            };
+            if is_left { entries[index].left = child } else { entries[index].right = child }
+        }
+    }
+    entries
+}
+
+pub fn write_tree<N: DotNode>(out: &mut impl fmt::Write, root: Option<N>, options: &PrintOptions) -> fmt::Result {
+    let Some(root) = root else { return writeln!(out, "Empty tree") };
+    if options.max_nodes == Some(0) {
+        return writeln!(out, "{}", ELLIPSIS);
+    }
+    let entries = collect(root, options);
+    match options.layout {
+        Layout::Sideways => write_sideways(out, &entries),
+        Layout::Vertical => write_vertical(out, &entries),
+    }
+}
+
+// the same as write_tree, for files, stdout and the like
+pub fn print_tree<N: DotNode>(out: &mut impl io::Write, root: Option<N>, options: &PrintOptions) -> io::Result<()> {
+    let mut adapter = IoAdapter { out, error: None };
+    write_tree(&mut adapter, root, options)
+        .map_err(|_| adapter.error.take().unwrap_or_else(|| io::Error::other("formatting failed")))
+}
+
+struct IoAdapter<'a, W> {
+    out: &'a mut W,
+    error: Option<io::Error>,
+}
+
+impl<W: io::Write> fmt::Write for IoAdapter<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.out.write_all(s.as_bytes()).map_err(|e| {
+            self.error = Some(e);
+            fmt::Error
+        })
+    }
+}
+
+// road is the way down from the root, u for a right child and d for a left one
+fn write_sideways(out: &mut impl fmt::Write, entries: &[Entry]) -> fmt::Result {
+    enum Item {
+        Expand(usize, String),
+        Line(Option<usize>, String),
+    }
+    // reversed in-order, right subtree first
+    let mut stack = vec![Item::Expand(0, String::new())];
+    while let Some(item) = stack.pop() {
+        let (index, road) = match item {
+            Item::Expand(index, road) => {
+                let entry = &entries[index];
+                let child = |child: Child, road: String| match child {
+                    Child::Node(child) => Some(Item::Expand(child, road)),
+                    Child::Elided => Some(Item::Line(None, road)),
+                    Child::None => None,
+                };
+                stack.extend(child(entry.left, road.clone() + "d"));
+                stack.push(Item::Line(Some(index), road.clone()));
+                stack.extend(child(entry.right, road + "u"));
+                continue;
+            }
+            Item::Line(index, road) => (index, road),
+        };
+
+        write!(out, "   ")?;
+        let steps = road.as_bytes();
+        for pair in steps.windows(2) {
+            write!(out, "{}", if pair[0] != pair[1] { "│  " } else { "   " })?;
+        }
+        match steps.last() {
+            Some(b'u') => write!(out, "╭──")?,
+            Some(_) => write!(out, "╰──")?,
+            None => {}
+        }
+        writeln!(out, "{}", index.map_or(ELLIPSIS, |index| &entries[index].label))?;
+    }
+    Ok(())
+}
+
+// every node gets its own columns in in-order, so subtrees never overlap
+fn write_vertical(out: &mut impl fmt::Write, entries: &[Entry]) -> fmt::Result {
+    // (label, width, depth) in in-order, with the place of each entry in it
+    let mut cells: Vec<(&str, usize, usize)> = Vec::new();
+    let mut place = vec![0; entries.len()];
+    let mut elided = vec![(None, None); entries.len()];
+    let mut stack = Vec::new();
+    let mut current = Some(0);
+    while current.is_some() || !stack.is_empty() {
+        while let Some(index) = current {
+            stack.push(index);
+            current = match entries[index].left {
+                Child::Node(left) => Some(left),
+                Child::Elided => {
+                    elided[index].0 = Some(cells.len());
+                    cells.push((ELLIPSIS, 1, entries[index].depth + 1));
+                    None
+                }
+                Child::None => None,
+            };
+        }
+        let index = stack.pop().unwrap();
+        place[index] = cells.len();
+        cells.push((&entries[index].label, entries[index].width, entries[index].depth));
+        current = match entries[index].right {
+            Child::Node(right) => Some(right),
+            Child::Elided => {
+                elided[index].1 = Some(cells.len());
+                cells.push((ELLIPSIS, 1, entries[index].depth + 1));
+                None
+            }
+            Child::None => None,
+        };
+    }
+
+    // starting column and middle of every cell, one blank column between neighbours
+    let mut starts = Vec::with_capacity(cells.len());
+    let mut column = 0;
+    for &(_, width, _) in &cells {
+        starts.push(column);
+        column += width + 1;
+    }
+    let middle = |cell: usize| starts[cell] + (cells[cell].1 - 1) / 2;
+    let depth = cells.iter().map(|&(_, _, depth)| depth).max().unwrap_or(0);
+
+    // a label fills its first column and leaves the rest empty, escapes and all
+    let mut rows = vec![vec![" "; column]; 2 * depth + 1];
+    for (cell, &(label, width, depth)) in cells.iter().enumerate() {
+        let row = &mut rows[2 * depth];
+        row[starts[cell]] = label;
+        row[starts[cell] + 1..starts[cell] + width].fill("");
+    }
+    for (index, entry) in entries.iter().enumerate() {
+        let child = |child: Child, elided: Option<usize>| match child {
+            Child::Node(child) => Some(middle(place[child])),
+            Child::Elided => elided.map(middle),
+            Child::None => None,
+        };
+        let (left, right) = (child(entry.left, elided[index].0), child(entry.right, elided[index].1));
+        if left.is_none() && right.is_none() {
+            continue;
+        }
+        let parent = middle(place[index]);
+        let row = &mut rows[2 * entry.depth + 1];
+        if let Some(left) = left {
+            row[left] = "┌";
+            row[left + 1..parent].fill("─");
+        }
+        if let Some(right) = right {
+            row[parent + 1..right].fill("─");
+            row[right] = "┐";
+        }
+        row[parent] = match (left, right) {
+            (Some(_), Some(_)) => "┴",
+            (Some(_), None) => "┘",
+            _ => "└",
+        };
+    }
+    for row in rows {
+        let end = row.iter().rposition(|cell| *cell != " ").map_or(0, |end| end + 1);
+        writeln!(out, "{}", row[..end].concat())?;
+    }
+    Ok(())
+}
@@ -1,8 +1,16 @@
-use std::cell::{RefCell, RefMut};
+use std::cell::RefCell;
 use std::fmt::{Debug, Formatter};
-use std::rc::Rc;
+use std::io;
+use std::rc::{Rc, Weak};
 
+use itertools::Itertools;
+
+use crate::dot::{to_dot, DotOptions};
 use crate::experiment::Stats;
+use crate::frames::Snapshot;
+use crate::printer::{print_tree, write_tree, PrintOptions};
+use crate::shape::{DepthHistogram, Shape};
+use crate::validate::{validate, InvariantError};
 use crate::rb_tree::node_pointer::{color, new_pointer, parent};
 use crate::rb_tree::tree_node::{Node, NodePointer};
 use crate::rb_tree::tree_node::Color::{Black, Red};
@@ -16,20 +24,314 @@ pub struct BinTree<T: Ord> {
     size: usize,
 }
 
+pub struct Iter<T: Ord> {
+    stack: Vec<NodePointer<T>>,
+}
+
+impl<T: Ord> Iter<T> {
+    fn push_left(&mut self, mut node: Option<NodePointer<T>>) {
+        while let Some(current) = node {
+            node = current.borrow().left.clone();
+            self.stack.push(current);
+        }
+    }
+
+    // past the next node without cloning its key, false at the end
+    fn advance(&mut self) -> bool {
+        let Some(node) = self.stack.pop() else { return false };
+        self.push_left(node.borrow().right.clone());
+        true
+    }
+}
+
+impl<T: Ord + Clone> Iterator for Iter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        let node = self.stack.pop()?;
+        let node_ref = node.borrow();
+        self.push_left(node_ref.right.clone());
+        Some(node_ref.val.clone())
+    }
+}
+
+impl<T: Ord + Debug> Default for BinTree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<T: Ord + Debug> BinTree<T> {
     pub fn new() -> Self {
         BinTree { root: None, nil: None, size: 0 }
     }
 
+    pub fn len(&self) -> usize {
+        self.size
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    // unlinks the nodes one at a time, dropping a long path in one go would recurse down all of it
+    pub fn clear(&mut self) {
+        let mut stack: Vec<NodePointer<T>> = self.root.take().into_iter().collect();
+        while let Some(node) = stack.pop() {
+            let mut node_ref = node.borrow_mut();
+            stack.extend(node_ref.left.take());
+            stack.extend(node_ref.right.take());
+        }
+        self.size = 0;
+        self.nil = None;
+    }
+
+    // balanced in O(n), the keys have to come in order, repeats allowed; all black but for
+    // the deepest level, which is red so the paths that end a level early have as many blacks
+    pub fn from_sorted_iter(iter: impl IntoIterator<Item = T>) -> Self {
+        let nodes = iter.into_iter().map(|val| new_pointer(val, Weak::new())).collect::<Vec<_>>();
+        assert!(nodes.is_sorted_by(|a, b| a.borrow().val <= b.borrow().val), "from_sorted_iter got keys out of order");
+        Self::from_nodes(nodes)
+    }
+
+    fn from_nodes(nodes: Vec<NodePointer<T>>) -> Self {
+        let size = nodes.len();
+        // a lone root stays black
+        let red = (size > 1).then(|| size.ilog2() as usize);
+        let root = build(&mut nodes.into_iter(), size, 0, red);
+        if let Some(root) = &root {
+            root.borrow_mut().parent = Rc::downgrade(root);
+        }
+        let tree = BinTree { root, nil: None, size };
+        #[cfg(feature = "check-invariants")]
+        tree.check("from_sorted_iter");
+        tree
+    }
+
+    // the nodes in order, unlinked from each other, leaving the tree empty
+    fn take_nodes(&mut self) -> Vec<NodePointer<T>> {
+        let mut nodes = Vec::with_capacity(self.size);
+        let mut stack = Vec::new();
+        let mut current = self.root.take();
+        loop {
+            while let Some(node) = current {
+                current = node.borrow_mut().left.take();
+                stack.push(node);
+            }
+            let Some(node) = stack.pop() else { break };
+            current = node.borrow_mut().right.take();
+            nodes.push(node);
+        }
+        self.size = 0;
+        self.nil = None;
+        nodes
+    }
+
+    // every key of left no greater than any of right; O(log n)
+    pub fn join(left: Self, mut right: Self) -> Self {
+        let (Some(left_root), Some(right_root)) = (&left.root, &right.root) else {
+            return if left.is_empty() { right } else { left };
+        };
+        let stats = &mut Stats::new();
+        let largest = max_from(left_root.clone(), stats);
+        let smallest = min_from(right_root.clone(), stats);
+        assert!(largest.borrow().val <= smallest.borrow().val, "join got a left tree with keys above the right one's");
+        // the smallest key on the right comes out to sit between the two
+        right.remove(smallest.clone(), stats);
+        let tree = Self::join_with(left, smallest, right);
+        #[cfg(feature = "check-invariants")]
+        tree.check("join");
+        tree
+    }
+
+    // black-height join: mid goes where the shorter tree meets the spine of the taller one
+    // at its black height, red, with the shorter tree below it, and the insert fixup takes
+    // care of a red parent; every key of left <= mid <= every key of right
+    fn join_with(left: Self, mid: NodePointer<T>, right: Self) -> Self {
+        let size = left.size + 1 + right.size;
+        let (left_height, right_height) = (black_height(&left.root), black_height(&right.root));
+        if left_height == right_height {
+            {
+                let mut mid_ref = mid.borrow_mut();
+                mid_ref.color = Black;
+                mid_ref.parent = Rc::downgrade(&mid);
+                mid_ref.left = left.root;
+                mid_ref.right = right.root;
+            }
+            for child in [&mid.borrow().left, &mid.borrow().right].into_iter().flatten() {
+                child.borrow_mut().parent = Rc::downgrade(&mid);
+            }
+            return BinTree { root: Some(mid), nil: None, size };
+        }
+
+        let tall_is_left = left_height > right_height;
+        let (mut tree, short, height) = if tall_is_left {
+            (left, right.root, right_height)
+        } else {
+            (right, left.root, left_height)
+        };
+        // down the spine facing the shorter tree to the first black node of its black height
+        let step = |node: &NodePointer<T>| if tall_is_left { node.borrow().right.clone() } else { node.borrow().left.clone() };
+        let mut above = tree.root.clone().unwrap();
+        let mut current = step(&above);
+        let mut current_height = black_height(&tree.root) - 1;
+        while current_height > height || color(&current) == Red {
+            let node = current.unwrap();
+            current_height -= usize::from(node.borrow().color == Black);
+            current = step(&node);
+            above = node;
+        }
+        {
+            let mut mid_ref = mid.borrow_mut();
+            mid_ref.color = Red;
+            mid_ref.parent = Rc::downgrade(&above);
+            (mid_ref.left, mid_ref.right) = if tall_is_left { (current, short) } else { (short, current) };
+        }
+        for child in [&mid.borrow().left, &mid.borrow().right].into_iter().flatten() {
+            child.borrow_mut().parent = Rc::downgrade(&mid);
+        }
+        if tall_is_left {
+            above.borrow_mut().right = Some(mid.clone());
+        } else {
+            above.borrow_mut().left = Some(mid.clone());
+        }
+        tree.insert_fixup(mid, &mut Stats::new());
+        tree.size = size;
+        tree
+    }
+
+    // the keys from key up move into the returned tree in O(min(k, n - k) + log² n) with k keys
+    // below key, so not in log time: the nodes keep no subtree sizes and the smaller half is walked
+    // to count it, which makes a split near the middle O(n). The log² n is cutting and rejoining
+    // the pieces, O(log n) joins on the way back up the search path, each finding its black
+    // heights in O(log n)
+    pub fn split_off(&mut self, key: &T) -> Self {
+        let size = self.size;
+        let (mut left, mut right) = split(self.root.take(), key);
+        let mut walks = [left.iter_nodes(), right.iter_nodes()];
+        let mut counted = 0;
+        let shorter = loop {
+            if let Some(shorter) = walks.iter_mut().position(|walk| !walk.advance()) {
+                break shorter;
+            }
+            counted += 1;
+        };
+        (left.size, right.size) = if shorter == 0 { (counted, size - counted) } else { (size - counted, counted) };
+        *self = left;
+        #[cfg(feature = "check-invariants")]
+        {
+            self.check("split_off");
+            right.check("split_off");
+        }
+        right
+    }
+
+    // moves every key of other into self, leaving it empty; O(log n) when all the keys of one
+    // tree are no greater than all of the other's, otherwise the two are merged and rebuilt in O(n)
+    pub fn append(&mut self, other: &mut Self) {
+        let (mut left, mut right) = (std::mem::take(self), std::mem::take(other));
+        let (Some(left_root), Some(right_root)) = (&left.root, &right.root) else {
+            *self = if left.is_empty() { right } else { left };
+            return;
+        };
+        let stats = &mut Stats::new();
+        let (left_min, left_max) = (min_from(left_root.clone(), stats), max_from(left_root.clone(), stats));
+        let (right_min, right_max) = (min_from(right_root.clone(), stats), max_from(right_root.clone(), stats));
+        *self = if left_max.borrow().val <= right_min.borrow().val {
+            Self::join(left, right)
+        } else if right_max.borrow().val <= left_min.borrow().val {
+            Self::join(right, left)
+        } else {
+            let nodes = left.take_nodes().into_iter().merge_by(right.take_nodes(), |a, b| a.borrow().val <= b.borrow().val);
+            Self::from_nodes(nodes.collect())
+        };
+    }
+
+    // iter without the Clone bound, for walking the nodes
+    fn iter_nodes(&self) -> Iter<T> {
+        let mut iter = Iter { stack: Vec::new() };
+        iter.push_left(self.root.clone());
+        iter
+    }
+
+    // a plain search, nothing is counted
+    pub fn contains(&self, val: &T) -> bool {
+        let mut current = self.root.clone();
+        while let Some(node) = current {
+            let node_ref = node.borrow();
+            current = match Ord::cmp(val, &node_ref.val) {
+                std::cmp::Ordering::Less => node_ref.left.clone(),
+                std::cmp::Ordering::Greater => node_ref.right.clone(),
+                std::cmp::Ordering::Equal => return true,
+            };
+        }
+        false
+    }
+
+    // the keys in order, cloned out of the nodes
+    pub fn iter(&self) -> Iter<T> where T: Clone {
+        self.iter_nodes()
+    }
+
     pub fn height(&self) -> usize {
         match &self.root {
             None => 0,
             Some(root) => root.borrow().measure_height()
         }
     }
+
+    pub fn depths(&self) -> DepthHistogram {
+        DepthHistogram::of(self.root.clone())
+    }
+
+    pub fn shape(&self) -> Shape {
+        Shape::of(self.root.clone())
+    }
+
+    // graphviz source of the tree, render it with `dot -Tsvg`
+    pub fn to_dot(&self, options: &DotOptions) -> String {
+        to_dot(self.root.clone(), options)
+    }
+
+    // ordering, parent pointers, the size count, the red-black rules and that nil hangs nowhere
+    pub fn validate(&self) -> Result<(), InvariantError> {
+        validate(self.root.clone(), Some(self.size), self.nil.as_ref().map(|nil| Rc::as_ptr(nil) as usize))
+    }
+
+    #[cfg(feature = "check-invariants")]
+    fn check(&self, op: &str) {
+        if let Err(e) = self.validate() {
+            panic!("{} left the tree broken: {}", op, e);
+        }
+    }
+
+    // drawn as text, see PrintOptions for the layouts and limits
+    pub fn print(&self, out: &mut impl io::Write, options: &PrintOptions) -> io::Result<()> {
+        print_tree(out, self.root.clone(), options)
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot::of(self.root.clone())
+    }
+
+    fn frame(&self, stats: &mut Stats, caption: &str) {
+        stats.frame(|| caption.to_string(), || self.snapshot());
+    }
+
     pub fn insert(&mut self, val: T, stats: &mut Stats) {
-        let mut x = self.insert_helper(val, stats);
+        let x = self.insert_helper(val, stats);
+        if self.check_root(&x) {
+            self.frame(stats, "new root");
+        } else {
+            self.frame(stats, "new red leaf");
+        }
+        self.insert_fixup(x, stats);
+        #[cfg(feature = "check-invariants")]
+        self.check("insert");
+    }
 
+    // x is red and may have a red parent, recolors and rotates up from there
+    fn insert_fixup(&mut self, mut x: NodePointer<T>, stats: &mut Stats) {
         while !self.check_root(&x)
             && parent(&x).borrow().color == Red {
             stats.read();
@@ -48,18 +350,21 @@ impl<T: Ord + Debug> BinTree<T> {
                     parent(&parent(&x)).borrow_mut().color = Red;
 
                     x = parent(&parent(&x));
+                    self.frame(stats, "insert case 1: uncle red, recolor parent, uncle and grandparent");
                 } else {
                     stats.read();
                     if self.comp_ptr(&x, &parent(&x).borrow().right) {
                         x = parent(&x);
 
                         self.left_rotate(&x, stats);
+                        self.frame(stats, "insert case 2: uncle black, left-rotate parent");
                     }
                     parent(&x).borrow_mut().color = Black;
 
                     parent(&parent(&x)).borrow_mut().color = Red;
 
                     self.right_rotate(&parent(&parent(&x)), stats);
+                    self.frame(stats, "insert case 3: uncle black, recolor and right-rotate grandparent");
                 }
             } else {
                 stats.read();
@@ -74,6 +379,7 @@ impl<T: Ord + Debug> BinTree<T> {
                     parent(&parent(&x)).borrow_mut().color = Red;
 
                     x = parent(&parent(&x));
+                    self.frame(stats, "insert case 1: uncle red, recolor parent, uncle and grandparent");
                 } else {
                     stats.read();
                     if self.comp_ptr(&x, &parent(&x).borrow().left) {
@@ -81,16 +387,22 @@ impl<T: Ord + Debug> BinTree<T> {
                         x = parent(&x);
 
                         self.right_rotate(&x, stats);
+                        self.frame(stats, "insert case 2: uncle black, right-rotate parent");
                     }
                     parent(&x).borrow_mut().color = Black;
 
                     parent(&parent(&x)).borrow_mut().color = Red;
 
                     self.left_rotate(&parent(&parent(&x)), stats);
+                    self.frame(stats, "insert case 3: uncle black, recolor and left-rotate grandparent");
                 }
             }
         }
+        let red_root = self.root.as_ref().unwrap().borrow().color == Red;
         self.root.as_ref().unwrap().borrow_mut().color = Black;
+        if red_root {
+            self.frame(stats, "recolor root black");
+        }
     }
 
     fn insert_helper(&mut self, val: T, stats: &mut Stats) -> NodePointer<T> {
@@ -108,7 +420,9 @@ impl<T: Ord + Debug> BinTree<T> {
             Some(root) => {
                 stats.read();
                 let mut current = root.clone();
+                let mut depth = 0;
                 loop {
+                    depth += 1;
                     current = {
                         let mut curr_ref = current.borrow_mut();
 
@@ -125,6 +439,7 @@ impl<T: Ord + Debug> BinTree<T> {
                                 child.clone()
                             }
                             None => {
+                                stats.depth(depth);
                                 stats.swap();
                                 *child = Some(new_pointer(val, Rc::downgrade(&current)));
                                 return child.clone().unwrap();
@@ -136,99 +451,6 @@ impl<T: Ord + Debug> BinTree<T> {
         }
     }
 
-    fn delete_old(&mut self, val: T, stats: &mut Stats) -> bool {
-        if self.root.is_none() {
-            return false;
-        } else {
-            // handling case when root is to be deleted
-            let mut comp;
-            {
-                stats.read();
-                let root_ref = self.root.as_ref().unwrap().borrow_mut();
-
-                stats.comp();
-                comp = Ord::cmp(&val, &root_ref.val);
-                if comp.is_eq() {
-                    // delete root
-                    let new = get_replacement(root_ref, stats);
-
-                    stats.swap();
-                    self.root = new;
-
-                    match &self.root {
-                        Some(new) => {
-                            stats.swap();
-                            new.borrow_mut().parent = Rc::downgrade(&self.root.as_ref().unwrap());
-                            new.borrow_mut().color = Black;
-                        }
-                        _ => {}
-                    }
-                    self.size -= 1;
-                    return true;
-                }
-            }
-
-            //when root is not the one to be deleted
-            stats.read();
-            let mut current = self.root.clone().unwrap();
-            let fix;
-            loop {
-                current = {
-                    let mut current_ref = current.borrow_mut();
-
-                    //because of the precious iteration we don't need to check if values are equal
-                    let child = if comp.is_lt() {
-                        &mut current_ref.left
-                    } else {
-                        &mut current_ref.right
-                    };
-
-                    match child {
-                        None => {
-                            return false;
-                        }
-                        Some(child_ptr) => {
-                            let child_ref = child_ptr.borrow_mut();
-
-                            stats.comp();
-                            //comparing values
-                            comp = Ord::cmp(&val, &child_ref.val);
-
-                            if comp.is_eq() {
-
-                                // delete
-                                let black = child_ref.color == Black;
-                                let new = get_replacement(child_ref, stats);
-
-                                if black {
-                                    fix = match &new {
-                                        Some(new) => Some(new.clone()),
-                                        None => Some(child_ptr.clone()),
-                                    };
-                                } else {
-                                    fix = None;
-                                }
-
-                                stats.swap();
-                                *child = new;
-
-                                self.size -= 1;
-                                break;
-                            } else {
-                                stats.read();
-                                child_ptr.clone()
-                            }
-                        }
-                    }
-                }
-            }
-            if let Some(fix) = fix {
-                self.delete_fixup(fix, stats);
-            }
-            return true;
-        }
-    }
-
     fn left_rotate(&mut self, node: &NodePointer<T>, stats: &mut Stats) {
         stats.read();
         let y = node.borrow().right.clone().unwrap();
@@ -252,14 +474,14 @@ impl<T: Ord + Debug> BinTree<T> {
 
             stats.swap();
             self.root = Some(y.clone());
-        } else if self.comp_ptr(&node, &parent(&node).borrow().left) {
+        } else if self.comp_ptr(node, &parent(node).borrow().left) {
             stats.read();
 
             stats.swap();
-            parent(&node).borrow_mut().left = Some(y.clone());
+            parent(node).borrow_mut().left = Some(y.clone());
         } else {
             stats.swap();
-            parent(&node).borrow_mut().right = Some(y.clone());
+            parent(node).borrow_mut().right = Some(y.clone());
         }
 
         stats.swap();
@@ -291,16 +513,16 @@ impl<T: Ord + Debug> BinTree<T> {
 
             stats.swap();
             self.root = Some(y.clone());
-        } else if self.comp_ptr(&node, &parent(&node).borrow().right) {
+        } else if self.comp_ptr(node, &parent(node).borrow().right) {
             stats.read();
 
             stats.swap();
-            parent(&node).borrow_mut().right = Some(y.clone());
+            parent(node).borrow_mut().right = Some(y.clone());
         } else {
             stats.read();
 
             stats.swap();
-            parent(&node).borrow_mut().left = Some(y.clone());
+            parent(node).borrow_mut().left = Some(y.clone());
         }
 
         y.borrow_mut().right = Some(node.clone());
@@ -309,43 +531,47 @@ impl<T: Ord + Debug> BinTree<T> {
 
 
     fn check_root(&self, node: &NodePointer<T>) -> bool {
-        Rc::ptr_eq(node, &parent(&node))
+        Rc::ptr_eq(node, &parent(node))
     }
 
     fn transplant(&mut self, u: &NodePointer<T>, v: &Option<NodePointer<T>>, stats: &mut Stats) {
         if let Some(v) = &v {
             stats.swap();
-            v.borrow_mut().parent = Rc::downgrade(&parent(&u));
+            v.borrow_mut().parent = Rc::downgrade(&parent(u));
             //println!("transplant {:?} with {:?}", u.borrow().val, v.borrow().val);
         } else {
             //println!("transplant {:?} with nil", u.borrow().val);
         }
 
         stats.read();
-        if self.check_root(&u) {
+        if self.check_root(u) {
             if let Some(v) = &v {
                 stats.swap();
-                v.borrow_mut().parent = Rc::downgrade(&v);
+                v.borrow_mut().parent = Rc::downgrade(v);
             }
             self.root = v.clone();
-        } else if self.comp_ptr(&u, &parent(&u).borrow().left) {
+        } else if self.comp_ptr(u, &parent(u).borrow().left) {
             stats.read();
 
-            parent(&u).borrow_mut().left = v.clone();
+            parent(u).borrow_mut().left = v.clone();
         } else {
             stats.read();
 
-            parent(&u).borrow_mut().right = v.clone();
+            parent(u).borrow_mut().right = v.clone();
         }
         stats.swap();
     }
 
+    // leaves the depth of the found node, or of the last one looked at, in stats
     fn search(&self, val: T, stats: &mut Stats) -> Option<NodePointer<T>> {
         let mut current = self.root.clone();
+        let mut depth = 0;
         loop {
             match current {
                 None => return None,
                 Some(node) => {
+                    stats.depth(depth);
+                    depth += 1;
                     let node_ref = node.borrow();
                     stats.comp();
                     stats.read();
@@ -360,96 +586,111 @@ impl<T: Ord + Debug> BinTree<T> {
     }
 
     pub fn delete(&mut self, val: T, stats: &mut Stats) -> bool {
-        let z = self.search(val, stats);
-        return match z {
+        match self.search(val, stats) {
             None => false,
             Some(z) => {
-                let mut y = z.clone();
-                let mut y_original_color = y.borrow().color.clone();
-                let mut x;
+                self.remove(z, stats);
+                #[cfg(feature = "check-invariants")]
+                self.check("delete");
+                true
+            }
+        }
+    }
 
-                stats.read();
-                if z.borrow().left.is_none() {
-                    stats.read();
-                    self.transplant(&z, &z.borrow().right, stats);
+    // unlinks z, which has to be in the tree, and restores the colors
+    fn remove(&mut self, z: NodePointer<T>, stats: &mut Stats) {
+        let mut y = z.clone();
+        let mut y_original_color = y.borrow().color.clone();
+        let mut x;
+        let removal;
 
-                    stats.read();
-                    x = if z.borrow().right.is_some() {
-                        z.borrow().right.clone().unwrap()
-                    } else {
-                        self.set_nil(z.clone(), stats)
-                    };
-                    // self.transplant(&z, &z.borrow().right);
-                } else if z.borrow().right.is_none() {
-                    stats.read();
+        stats.read();
+        if z.borrow().left.is_none() {
+            stats.read();
+            self.transplant(&z, &z.borrow().right, stats);
 
-                    stats.read();
-                    x = z.borrow().left.clone().unwrap();
+            stats.read();
+            x = if z.borrow().right.is_some() {
+                removal = "delete: node replaced by its right child";
+                z.borrow().right.clone().unwrap()
+            } else {
+                removal = "delete: leaf removed";
+                self.set_nil(z.clone(), stats)
+            };
+            // self.transplant(&z, &z.borrow().right);
+        } else if z.borrow().right.is_none() {
+            stats.read();
 
-                    stats.read();
-                    self.transplant(&z, &z.borrow().left, stats);
-                } else {
-                    stats.read();
-                    y = min_from(z.borrow().right.clone().unwrap(), stats);
+            stats.read();
+            x = z.borrow().left.clone().unwrap();
 
-                    y_original_color = y.borrow().color.clone();
+            stats.read();
+            self.transplant(&z, &z.borrow().left, stats);
+            removal = "delete: node replaced by its left child";
+        } else {
+            stats.read();
+            y = min_from(z.borrow().right.clone().unwrap(), stats);
 
-                    let mut fix_x = false;
+            y_original_color = y.borrow().color.clone();
 
-                    stats.read();
-                    x = if y.borrow().right.is_some() {
-                        y.borrow().right.clone().unwrap()
-                    } else {
-                        fix_x = true;
-                        parent(&y)
-                        //self.set_nil(y.clone())
-                    };
+            let mut fix_x = false;
 
-                    stats.read();
-                    if Rc::ptr_eq(&parent(&y), &z) {
-                        if !fix_x {
-                            stats.swap();
-                            x.borrow_mut().parent = Rc::downgrade(&y);
-                        } else {
-                            x = y.clone()
-                        }
-                    } else {
-                        stats.read();
-                        self.transplant(&y, &y.borrow().right, stats);
+            stats.read();
+            x = if y.borrow().right.is_some() {
+                y.borrow().right.clone().unwrap()
+            } else {
+                fix_x = true;
+                parent(&y)
+                //self.set_nil(y.clone())
+            };
 
-                        stats.swap();
-                        y.borrow_mut().right = z.borrow().right.clone();
+            stats.read();
+            if Rc::ptr_eq(&parent(&y), &z) {
+                if !fix_x {
+                    stats.swap();
+                    x.borrow_mut().parent = Rc::downgrade(&y);
+                } else {
+                    x = y.clone()
+                }
+            } else {
+                stats.read();
+                self.transplant(&y, &y.borrow().right, stats);
 
-                        stats.swap();
-                        y.borrow().right.as_ref().unwrap().borrow_mut().parent = Rc::downgrade(&y);
-                    }
-                    self.transplant(&z, &Some(y.clone()), stats);
+                stats.swap();
+                y.borrow_mut().right = z.borrow().right.clone();
 
-                    stats.swap();
-                    y.borrow_mut().left = z.borrow().left.clone();
+                stats.swap();
+                y.borrow().right.as_ref().unwrap().borrow_mut().parent = Rc::downgrade(&y);
+            }
+            self.transplant(&z, &Some(y.clone()), stats);
 
-                    stats.swap();
-                    y.borrow().left.as_ref().unwrap().borrow_mut().parent = Rc::downgrade(&y);
+            stats.swap();
+            y.borrow_mut().left = z.borrow().left.clone();
 
-                    stats.swap();
-                    y.borrow_mut().color = z.borrow().color.clone();
+            stats.swap();
+            y.borrow().left.as_ref().unwrap().borrow_mut().parent = Rc::downgrade(&y);
 
-                    if fix_x {
-                        let nil = self.set_nil(z, stats);
+            stats.swap();
+            y.borrow_mut().color = z.borrow().color.clone();
 
-                        stats.swap();
-                        nil.borrow_mut().parent = Rc::downgrade(&x);
-                        //println!("nil parent: {:?}", nil.borrow().parent.upgrade().unwrap().borrow().val);
-                        x = nil;
-                    }
-                }
+            if fix_x {
+                let nil = self.set_nil(z, stats);
 
-                if y_original_color == Black {
-                    self.delete_fixup(x, stats);
-                }
-                true
+                stats.swap();
+                nil.borrow_mut().parent = Rc::downgrade(&x);
+                //println!("nil parent: {:?}", nil.borrow().parent.upgrade().unwrap().borrow().val);
+                x = nil;
             }
-        };
+            removal = "delete: node replaced by its successor";
+        }
+        self.frame(stats, removal);
+
+        if y_original_color == Black {
+            self.delete_fixup(x, stats);
+        }
+        self.size -= 1;
+        // the fixup is done with the stand-in, holding on to it would keep the removed node alive
+        self.nil = None;
     }
 
     fn delete_fixup(&mut self, mut x: NodePointer<T>, stats: &mut Stats) {
@@ -479,6 +720,7 @@ impl<T: Ord + Debug> BinTree<T> {
 
                     stats.read();
                     w = parent(&x).borrow().right.clone();
+                    self.frame(stats, "delete case 1: sibling red, recolor and left-rotate parent");
                 }
 
                 //case 2
@@ -490,6 +732,7 @@ impl<T: Ord + Debug> BinTree<T> {
 
                     stats.read();
                     x = parent(&x);
+                    self.frame(stats, "delete case 2: sibling and its children black, recolor sibling red");
                 } else {
 
                     //case 3
@@ -504,6 +747,7 @@ impl<T: Ord + Debug> BinTree<T> {
 
                         stats.read();
                         w = parent(&x).borrow().right.clone();
+                        self.frame(stats, "delete case 3: sibling's far child black, recolor and right-rotate sibling");
                     }
                     //case 4
                     w.as_ref().unwrap().borrow_mut().color = parent(&x).borrow().color.clone();
@@ -519,6 +763,7 @@ impl<T: Ord + Debug> BinTree<T> {
 
                     stats.read();
                     x = self.root.clone().unwrap();
+                    self.frame(stats, "delete case 4: sibling's far child red, recolor and left-rotate parent");
                 }
             } else {
                 //println!("{:?} is right child", x.borrow().val);
@@ -536,6 +781,7 @@ impl<T: Ord + Debug> BinTree<T> {
 
                     stats.read();
                     w = parent(&x).borrow().left.clone();
+                    self.frame(stats, "delete case 1: sibling red, recolor and right-rotate parent");
                 }
 
                 stats.read();
@@ -546,6 +792,7 @@ impl<T: Ord + Debug> BinTree<T> {
 
                     stats.read();
                     x = parent(&x);
+                    self.frame(stats, "delete case 2: sibling and its children black, recolor sibling red");
                 } else {
                     if color(&w.as_ref().unwrap().borrow().left) == Black {
                         stats.read();
@@ -557,6 +804,7 @@ impl<T: Ord + Debug> BinTree<T> {
 
                         stats.read();
                         w = parent(&x).borrow().left.clone();
+                        self.frame(stats, "delete case 3: sibling's far child black, recolor and left-rotate sibling");
                     }
                     stats.read();
                     w.as_ref().unwrap().borrow_mut().color = parent(&x).borrow().color.clone();
@@ -571,10 +819,15 @@ impl<T: Ord + Debug> BinTree<T> {
 
                     stats.read();
                     x = self.root.clone().unwrap();
+                    self.frame(stats, "delete case 4: sibling's far child red, recolor and right-rotate parent");
                 }
             }
         }
+        let red = x.borrow().color == Red;
         x.borrow_mut().color = Black;
+        if red {
+            self.frame(stats, "delete: the node carrying the extra black is red, recolor it black");
+        }
     }
 
     fn set_nil(&mut self, node: NodePointer<T>, stats: &mut Stats) -> NodePointer<T> {
@@ -618,104 +871,95 @@ impl<T: Ord + Debug> BinTree<T> {
     }
 }
 
+impl<T: Debug + Ord> Debug for BinTree<T> {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write_tree(f, self.root.clone(), &PrintOptions::default())
+    }
+}
 
-fn get_replacement<T: Ord>(mut node: RefMut<Node<T>>, stats: &mut Stats) -> Option<NodePointer<T>>
-{
-    let mut new =
-        match (&node.left, &node.right) {
-            (Some(_left), Some(right)) => {
-                let replacement = min_from(right.clone(), stats);
-                {
-                    let mut replacement_ref = replacement.borrow_mut();
-
-                    //connecting replacement's children to replacement's parent
-                    stats.read();
-                    stats.swap();
-                    if Rc::ptr_eq(&replacement, right) {
-                        node.right = replacement_ref.right.clone();
-                    } else {
-                        replacement_ref.parent.upgrade().unwrap().borrow_mut().left = replacement_ref.right.clone();
-                    }
-
-                    //setting correct parent in replacement's former child
-                    if let Some(new_right) = &replacement_ref.right {
-                        stats.swap();
-                        new_right.borrow_mut().parent = replacement_ref.parent.clone();
-                    }
-
-                    // if let Some(new_left) = &replacement_ref.left {
-                    //     new_left.borrow_mut().parent = replacement_ref.parent.clone();
-                    // }
-
-                    stats.swap();
-                    stats.swap();
-                    //setting replacement's children to be the same as node's children
-                    replacement_ref.left = node.left.clone();
-                    replacement_ref.right = node.right.clone();
-                    replacement_ref.color = node.color.clone();
-
+// sorted input at least as long as the tree is merged into it in one pass and the whole
+// tree rebuilt, anything else goes in one insert at a time
+impl<T: Ord + Debug> Extend<T> for BinTree<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let vals = iter.into_iter().collect::<Vec<_>>();
+        if vals.is_sorted() && vals.len() >= self.size {
+            let new = vals.into_iter().map(|val| new_pointer(val, Weak::new()));
+            let nodes = self.take_nodes().into_iter().merge_by(new, |a, b| a.borrow().val <= b.borrow().val).collect();
+            *self = Self::from_nodes(nodes);
+        } else {
+            let stats = &mut Stats::new();
+            vals.into_iter().for_each(|val| self.insert(val, stats));
+        }
+    }
+}
 
-                    //setting correct parent in replacement's children
-                    if let Some(left) = &replacement_ref.left {
-                        stats.swap();
-                        left.borrow_mut().parent = Rc::downgrade(&replacement);
-                    }
-                    if let Some(right) = &replacement_ref.right {
-                        stats.swap();
-                        right.borrow_mut().parent = Rc::downgrade(&replacement);
-                    }
-                }
+impl<T: Ord + Debug> FromIterator<T> for BinTree<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut tree = Self::new();
+        tree.extend(iter);
+        tree
+    }
+}
 
-                Some(replacement)
-            }
-            (Some(left), None) => {
-                stats.swap();
-                let ret = Some(left.clone());
-                node.left = None;
-                ret
-            }
-            (None, Some(right)) => {
-                stats.swap();
-                let ret = Some(right.clone());
-                node.right = None;
-                ret
-            }
-            (None, None) => {
-                None
-            }
-        };
+// black nodes on the way down the left spine, every way down has as many
+fn black_height<T: Ord>(root: &Option<NodePointer<T>>) -> usize {
+    let mut height = 0;
+    let mut current = root.clone();
+    while let Some(node) = current {
+        height += usize::from(node.borrow().color == Black);
+        current = node.borrow().left.clone();
+    }
+    height
+}
 
-    if let Some(new) = &mut new {
-        let mut new_ref = new.borrow_mut();
+// a subtree cut loose as a tree of its own, its root black; the size is left for the caller
+fn detach<T: Ord + Debug>(node: Option<NodePointer<T>>) -> BinTree<T> {
+    if let Some(node) = &node {
+        let mut node_ref = node.borrow_mut();
+        node_ref.parent = Rc::downgrade(node);
+        node_ref.color = Black;
+    }
+    BinTree { root: node, nil: None, size: 0 }
+}
 
-        stats.swap();
-        //setting correct parent in replacement
-        new_ref.parent = node.parent.clone();
-        // new_ref.left = node.left.clone();
-        // new_ref.right = node.right.clone();
+// keys below key to the left, the rest to the right, joining the pieces back together
+// on the way up; the sizes are left for the caller
+fn split<T: Ord + Debug>(node: Option<NodePointer<T>>, key: &T) -> (BinTree<T>, BinTree<T>) {
+    let Some(node) = node else { return (BinTree::new(), BinTree::new()) };
+    let (left, right) = {
+        let mut node_ref = node.borrow_mut();
+        (detach(node_ref.left.take()), detach(node_ref.right.take()))
     };
-
-    new
+    if *key <= node.borrow().val {
+        let (below, above) = split(left.root, key);
+        (below, BinTree::join_with(above, node, right))
+    } else {
+        let (below, above) = split(right.root, key);
+        (BinTree::join_with(left, node, below), above)
+    }
 }
 
-
-impl<T: Debug + Ord> Debug for BinTree<T> {
-    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
-        if let Some(root) = &self.root {
-            root.borrow().print_node(f, String::new());
-        } else {
-            write!(f, "Empty tree")?;
-        }
-        Ok(())
+// the next n nodes as a balanced subtree, the middle one at its root, red at depth red
+fn build<T: Ord>(nodes: &mut impl Iterator<Item = NodePointer<T>>, n: usize, depth: usize, red: Option<usize>) -> Option<NodePointer<T>> {
+    if n == 0 {
+        return None;
     }
+    let left = build(nodes, (n - 1) / 2, depth + 1, red);
+    let node = nodes.next().unwrap();
+    let right = build(nodes, n / 2, depth + 1, red);
+    for child in left.iter().chain(&right) {
+        child.borrow_mut().parent = Rc::downgrade(&node);
+    }
+    let mut node_ref = node.borrow_mut();
+    node_ref.color = if red == Some(depth) { Red } else { Black };
+    node_ref.left = left;
+    node_ref.right = right;
+    drop(node_ref);
+    Some(node)
 }
 
 fn max_from<T: Ord>(start: NodePointer<T>, stats: &mut Stats) -> NodePointer<T> {
     let mut current = start;
-    // while let Some(right) = current.clone().borrow().right.clone() {
-    //     current = right;
-    // }
-
     loop {
         current = {
             let current_ref = current.borrow();
@@ -1,17 +1,17 @@
 use std::cell::RefCell;
-use std::fmt::{Debug, Formatter};
+use std::cmp::Ordering;
+use std::fmt::Debug;
 use std::rc::{Rc, Weak};
-use itertools::Itertools;
+use crate::dot::DotNode;
+use crate::live::Live;
+use crate::shape::NodeView;
+use crate::structure::StructureKind;
+use crate::validate::CheckedNode;
 
 
 pub(super) type NodePointer<T> = Rc<RefCell<Node<T>>>;
 pub(super) type NodeWeak<T> = Weak<RefCell<Node<T>>>;
 
-pub enum Direction {
-    Left,
-    Right,
-}
-
 #[derive(Debug)]
 #[derive(PartialEq)]
 #[derive(Clone)]
@@ -26,6 +26,8 @@ pub struct Node<T: Ord> {
     pub(super) color: Color,
     pub(super) left: Option<NodePointer<T>>,
     pub(super) right: Option<NodePointer<T>>,
+    // held for its drop
+    _live: Live,
 }
 
 impl<T: Ord> Node<T> {
@@ -36,13 +38,10 @@ impl<T: Ord> Node<T> {
             color: Color::Red,
             left: None,
             right: None,
+            _live: Live::new(StructureKind::RbTree),
         }
     }
 
-    pub fn val(&self) -> &T {
-        &self.val
-    }
-
     pub(super) fn measure_height(&self) -> usize {
         let left_height = match &self.left {
             Some(left) => left.borrow().measure_height(),
@@ -61,44 +60,6 @@ impl<T: Ord> Node<T> {
 }
 
 impl<T: Ord + Debug> Node<T> {
-    pub(super) fn print_node(&self, f: &mut Formatter, road: String) {
-        if let Some(right) = &self.right {
-            right.borrow().print_node(f, road.clone() + "u");
-        }
-
-        write!(f, "   ").unwrap();
-        for (x, y) in road.chars().tuple_windows() {
-            if x != y {
-                write!(f, "│  ").unwrap();
-            } else {
-                write!(f, "   ").unwrap();
-            }
-        }
-        if let Some(last) = road.chars().last() {
-            if last == 'u' {
-                write!(f, "╭──").unwrap();
-            } else {
-                write!(f, "╰──").unwrap();
-            }
-        }
-        let val = format!("{:?}", &self.val);
-        match self.color {
-            Color::Red => write!(f, "\x1b[31m{}\x1b[0m", val).unwrap(),
-            Color::Black => write!(f, "{}", val).unwrap(),
-        }
-        // let binding = self.parent.upgrade();
-        // let parent = match binding {
-        //     Some(parent) => format!("{:?}", parent.borrow().val),
-        //     None => "error".to_string(),
-        // };
-        // write!(f, " p{:?}", parent).unwrap();
-        write!(f, "\n").unwrap();
-
-        if let Some(left) = &self.left {
-            left.borrow().print_node(f, road + "d");
-        }
-    }
-
     pub(crate) fn print_road(&self) {
         print!("{:?} ", &self.val);
         if let Some(left) = &self.left {
@@ -114,3 +75,36 @@ impl<T: Ord + Debug> Node<T> {
     }
 }
 
+impl<T: Ord> NodeView for NodePointer<T> {
+    fn left(&self) -> Option<Self> {
+        self.borrow().left.clone()
+    }
+
+    fn right(&self) -> Option<Self> {
+        self.borrow().right.clone()
+    }
+
+    fn red(&self) -> Option<bool> {
+        Some(self.borrow().color == Color::Red)
+    }
+
+    fn id(&self) -> usize {
+        Rc::as_ptr(self) as usize
+    }
+
+    fn parent(&self) -> Option<Option<Self>> {
+        Some(self.borrow().parent.upgrade())
+    }
+}
+
+impl<T: Ord + Debug> DotNode for NodePointer<T> {
+    fn key(&self) -> String {
+        format!("{:?}", self.borrow().val)
+    }
+}
+
+impl<T: Ord> CheckedNode for NodePointer<T> {
+    fn cmp_key(&self, other: &Self) -> Ordering {
+        self.borrow().val.cmp(&other.borrow().val)
+    }
+}
@@ -0,0 +1,118 @@
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::chart::{draw_grid, draw_series, ChartError, ChartOptions, Panel};
+use crate::experiment::{Data, Metric};
+use crate::results::Saved;
+use crate::runner::{metric_series, Results};
+
+const PHASES: [&str; 2] = ["insert", "delete"];
+
+// the structures a metric can be shown for
+fn shown(results: &[Results], metric: Metric) -> Vec<&Results> {
+    results.iter().filter(|r| !metric.tree_only() || r.structure.is_tree()).collect()
+}
+
+fn phase_data<'a>(results: &'a Results, phase: &str) -> &'a [Data] {
+    if phase == "insert" { &results.insert } else { &results.delete }
+}
+
+// a grid of one panel per metric for each phase, the single comparison charts,
+// and a {name}_index.md linking them with a table of the values at the largest n
+pub fn write_report(saved: &Saved, metrics: &[Metric], options: &ChartOptions) -> io::Result<PathBuf> {
+    let name = &saved.metadata.name;
+    let Some(first) = saved.results.first() else {
+        return Err(io::Error::other(format!("{}: no results to report", name)));
+    };
+    let sizes = first.sizes.iter().copied();
+    let mut grids = Vec::new();
+    let mut charts = Vec::new();
+
+    for phase in PHASES {
+        let panels = metrics.iter()
+            .map(|&metric| Panel {
+                title: metric.name().to_string(),
+                y_label: metric.name().to_string(),
+                series: shown(&saved.results, metric).into_iter()
+                    .map(|r| metric_series(r.structure.name(), metric, phase_data(r, phase), options.wants_bands()))
+                    .collect(),
+            })
+            .collect();
+        let options = options.clone().title(format!("{} {}", name, phase));
+        match draw_grid(panels, sizes.clone(), &format!("{}_{}_report", name, phase), &options) {
+            Ok(file) => grids.extend(file.map(|file| (phase, file))),
+            Err(ChartError::Empty(_)) => {}
+            Err(e) => return Err(io::Error::other(e)),
+        }
+
+        for &metric in metrics {
+            let series = shown(&saved.results, metric).into_iter()
+                .map(|r| metric_series(r.structure.name(), metric, phase_data(r, phase), options.wants_bands()))
+                .collect();
+            let options = options.clone().title(format!("{} {} {}", name, phase, metric.name())).y_label(metric.name());
+            // metrics that weren't measured, like times of a run without timing, are left out
+            match draw_series(series, sizes.clone(), &format!("{}_{}_{}", name, phase, metric.key()), &options) {
+                Ok(file) => charts.extend(file.map(|file| (phase, metric, file))),
+                Err(ChartError::Empty(_)) => {}
+                Err(e) => return Err(io::Error::other(e)),
+            }
+        }
+    }
+
+    fs::create_dir_all(options.out_dir())?;
+    let index = options.out_dir().join(format!("{}_index.md", name));
+    fs::write(&index, index_page(saved, metrics, &grids, &charts))?;
+    Ok(index)
+}
+
+fn link(file: &Path) -> String {
+    file.file_name().map(|f| f.to_string_lossy().into_owned()).unwrap_or_default()
+}
+
+fn index_page(saved: &Saved, metrics: &[Metric], grids: &[(&str, PathBuf)], charts: &[(&str, Metric, PathBuf)]) -> String {
+    let meta = &saved.metadata;
+    let mut page = String::new();
+    writeln!(page, "# {}\n", meta.name).unwrap();
+    writeln!(page, "workload {}, seed {}, reps {}, revision {}\n",
+             meta.workload, meta.seed, meta.reps, meta.revision.as_deref().unwrap_or("unknown")).unwrap();
+
+    for phase in PHASES {
+        writeln!(page, "## {}\n", phase).unwrap();
+        for (_, file) in grids.iter().filter(|(p, _)| *p == phase) {
+            writeln!(page, "![{} report]({})\n", phase, link(file)).unwrap();
+        }
+
+        write!(page, "| structure | n |").unwrap();
+        for metric in metrics {
+            write!(page, " {} |", metric.name()).unwrap();
+        }
+        write!(page, "\n|---|---:|").unwrap();
+        for _ in metrics {
+            write!(page, "---:|").unwrap();
+        }
+        writeln!(page).unwrap();
+        for results in &saved.results {
+            let data = phase_data(results, phase);
+            let (Some(&n), Some(last)) = (results.sizes.last(), data.last()) else { continue };
+            write!(page, "| {} | {} |", results.structure, n).unwrap();
+            for metric in metrics {
+                let value = metric.value(last);
+                if (metric.tree_only() && !results.structure.is_tree()) || !value.is_finite() {
+                    write!(page, " - |").unwrap();
+                } else {
+                    write!(page, " {:.2} |", value).unwrap();
+                }
+            }
+            writeln!(page).unwrap();
+        }
+        writeln!(page).unwrap();
+
+        for (_, metric, file) in charts.iter().filter(|(p, _, _)| *p == phase) {
+            writeln!(page, "- [{}]({})", metric.name(), link(file)).unwrap();
+        }
+        writeln!(page).unwrap();
+    }
+    page
+}
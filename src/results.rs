@@ -0,0 +1,209 @@
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::process::Command;
+
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+use crate::chart::{ChartError, ChartOptions};
+use crate::config::Config;
+use crate::experiment::{Data, Metric};
+use crate::runner::{draw_comparison, draw_structure_charts, Outcome, Results};
+use crate::workload::Workload;
+
+// everything needed to tell where a results file came from
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Metadata {
+    pub name: String,
+    pub workload: Workload,
+    pub seed: u64,
+    pub reps: usize,
+    #[serde(default)]
+    pub revision: Option<String>,
+}
+
+// raw per-n data of a finished run, written as json or csv and read back for plotting
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Saved {
+    pub metadata: Metadata,
+    pub results: Vec<Results>,
+}
+
+impl Saved {
+    pub fn new(config: &Config, outcome: &Outcome) -> Self {
+        Saved {
+            metadata: Metadata {
+                name: config.name.clone(),
+                workload: config.workload,
+                seed: outcome.seed,
+                reps: config.reps,
+                revision: git_revision(),
+            },
+            results: outcome.results.clone(),
+        }
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("csv") => Self::from_csv(&fs::read_to_string(path)?),
+            _ => {
+                let file = fs::File::open(path)?;
+                Ok(serde_json::from_reader(io::BufReader::new(file))?)
+            }
+        }
+    }
+
+    pub fn save_json(&self, path: &Path) -> io::Result<()> {
+        let file = fs::File::create(path)?;
+        serde_json::to_writer_pretty(io::BufWriter::new(file), self)?;
+        Ok(())
+    }
+
+    pub fn save_csv(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, self.to_csv())
+    }
+
+    pub fn to_csv(&self) -> String {
+        let meta = &self.metadata;
+        let mut out = String::new();
+        writeln!(out, "# name: {}", meta.name).unwrap();
+        writeln!(out, "# workload: {}", meta.workload).unwrap();
+        writeln!(out, "# seed: {}", meta.seed).unwrap();
+        writeln!(out, "# reps: {}", meta.reps).unwrap();
+        if let Some(revision) = &meta.revision {
+            writeln!(out, "# revision: {}", revision).unwrap();
+        }
+        writeln!(out, "structure,phase,n,{},phase_nanos", Data::FIELDS.join(",")).unwrap();
+
+        for results in &self.results {
+            for (phase, data) in [("insert", &results.insert), ("delete", &results.delete)] {
+                for (n, data) in results.sizes.iter().zip(data) {
+                    writeln!(out, "{},{},{},{}", results.structure, phase, n, data_columns(data)).unwrap();
+                }
+            }
+        }
+        out
+    }
+
+    pub fn from_csv(text: &str) -> io::Result<Self> {
+        let invalid = |line: usize, msg: String| {
+            io::Error::new(io::ErrorKind::InvalidData, format!("line {}: {}", line + 1, msg))
+        };
+
+        let mut name = None;
+        let mut workload = None;
+        let mut seed = None;
+        let mut reps = None;
+        let mut revision = None;
+        let mut results: Vec<Results> = Vec::new();
+
+        for (i, line) in text.lines().enumerate() {
+            if let Some(comment) = line.strip_prefix('#') {
+                let Some((key, value)) = comment.split_once(':') else { continue };
+                let value = value.trim();
+                let parse_err = |e: String| invalid(i, format!("bad {}: {}", key.trim(), e));
+                match key.trim() {
+                    "name" => name = Some(value.to_string()),
+                    "workload" => workload = Some(value.parse().map_err(parse_err)?),
+                    "seed" => seed = Some(value.parse().map_err(|e: std::num::ParseIntError| parse_err(e.to_string()))?),
+                    "reps" => reps = Some(value.parse().map_err(|e: std::num::ParseIntError| parse_err(e.to_string()))?),
+                    "revision" => revision = Some(value.to_string()),
+                    _ => {}
+                }
+                continue;
+            }
+            if line.trim().is_empty() || line.starts_with("structure,") {
+                continue;
+            }
+
+            let cols = line.split(',').map(str::trim).collect::<Vec<_>>();
+            if cols.len() != 4 + Data::FIELDS.len() {
+                return Err(invalid(i, format!("expected {} columns, got {}", 4 + Data::FIELDS.len(), cols.len())));
+            }
+            let structure = cols[0].parse().map_err(|e| invalid(i, e))?;
+            let n = cols[2].parse::<usize>().map_err(|e| invalid(i, e.to_string()))?;
+            let data = parse_data_columns(&cols[3..]).map_err(|e| invalid(i, e))?;
+
+            let index = match results.iter().position(|r| r.structure == structure) {
+                Some(index) => index,
+                None => {
+                    results.push(Results { structure, sizes: Vec::new(), insert: Vec::new(), delete: Vec::new() });
+                    results.len() - 1
+                }
+            };
+            let results = &mut results[index];
+            match cols[1] {
+                "insert" => {
+                    results.sizes.push(n);
+                    results.insert.push(data);
+                }
+                "delete" => {
+                    if results.sizes.get(results.delete.len()) != Some(&n) {
+                        return Err(invalid(i, format!("delete row for n = {} has no matching insert row", n)));
+                    }
+                    results.delete.push(data);
+                }
+                phase => return Err(invalid(i, format!("unknown phase `{}`", phase))),
+            }
+        }
+
+        // every insert row has to have come with its delete row, or the two series would not line up
+        if let Some(short) = results.iter().find(|r| r.delete.len() != r.insert.len()) {
+            let message = format!("{} has {} insert rows but {} delete rows", short.structure, short.insert.len(), short.delete.len());
+            return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+        }
+
+        let missing = |what: &str| io::Error::new(io::ErrorKind::InvalidData, format!("missing `# {}:` line", what));
+        Ok(Saved {
+            metadata: Metadata {
+                name: name.ok_or_else(|| missing("name"))?,
+                workload: workload.ok_or_else(|| missing("workload"))?,
+                seed: seed.ok_or_else(|| missing("seed"))?,
+                reps: reps.ok_or_else(|| missing("reps"))?,
+                revision,
+            },
+            results,
+        })
+    }
+
+    pub fn draw_charts(&self, metrics: &[Metric], options: &ChartOptions) -> Result<(), ChartError> {
+        draw_structure_charts(&self.metadata.name, &self.results, metrics, options)
+    }
+
+    pub fn draw_comparison(&self, metrics: &[Metric], options: &ChartOptions) -> Result<(), ChartError> {
+        draw_comparison(&self.metadata.name, &self.results, metrics, options)
+    }
+}
+
+// the Data part of a csv row: its flat fields, then the phase times separated by spaces
+pub(crate) fn data_columns(data: &Data) -> String {
+    format!("{},{}", data.to_fields().iter().join(","), data.phase_nanos().iter().join(" "))
+}
+
+pub(crate) fn parse_data_columns(cols: &[&str]) -> Result<Data, String> {
+    if cols.len() != Data::FIELDS.len() + 1 {
+        return Err(format!("expected {} data columns, got {}", Data::FIELDS.len() + 1, cols.len()));
+    }
+    let mut fields = [0; Data::FIELDS.len()];
+    for (field, col) in fields.iter_mut().zip(cols) {
+        *field = col.trim().parse().map_err(|e: std::num::ParseIntError| e.to_string())?;
+    }
+    let phase_nanos = cols[cols.len() - 1].split_whitespace()
+        .map(|x| x.parse::<usize>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(Data::from_fields(fields, phase_nanos))
+}
+
+// short hash of the checked out commit, None outside a git checkout; uncommitted changes
+// add a -dirty suffix, the counts then come from no commit and the hash alone would say otherwise
+pub fn git_revision() -> Option<String> {
+    let output = Command::new("git").args(["describe", "--always", "--dirty", "--abbrev=7"]).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let revision = String::from_utf8(output.stdout).ok()?.trim().to_string();
+    (!revision.is_empty()).then_some(revision)
+}
@@ -0,0 +1,303 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use rayon::prelude::*;
+use rayon::ThreadPoolBuilder;
+use serde::{Deserialize, Serialize};
+
+use crate::chart::{draw_series, Band, ChartError, ChartOptions, Series};
+use crate::checkpoint::Checkpoint;
+use crate::config::Config;
+use crate::experiment::{Data, Metric, Stats};
+use crate::report::write_report;
+use crate::results::Saved;
+use crate::structure::{Structure, StructureKind};
+use crate::workload::{cell_seed, Sequence};
+
+// per-n results of one structure, in the order of the size sweep
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Results {
+    pub structure: StructureKind,
+    pub sizes: Vec<usize>,
+    pub insert: Vec<Data>,
+    pub delete: Vec<Data>,
+}
+
+pub struct Outcome {
+    pub seed: u64,
+    pub results: Vec<Results>,
+}
+
+// one cell of the sweep: a structure run on the sequence of one (n, rep)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Job {
+    pub structure: StructureKind,
+    pub n: usize,
+    pub rep: usize,
+}
+
+impl Job {
+    // the same (n, rep) gives every structure the same keys
+    pub fn run(&self, config: &Config, seed: u64) -> (Data, Data) {
+        let sequence = config.workload.generate(self.n, cell_seed(seed, self.n, self.rep));
+        run_cell(self.structure, &sequence, config.measure())
+    }
+}
+
+// runs the whole sweep on `config.threads` threads; with a checkpoint file configured, finished
+// cells are appended to it as they complete and cells already in it are not run again
+pub fn run(config: &Config) -> io::Result<Outcome> {
+    let checkpoint = match &config.outputs.checkpoint {
+        Some(path) => {
+            let checkpoint = Checkpoint::open(path, config)?;
+            if checkpoint.completed() > 0 {
+                println!("resuming: {} cells already in {}", checkpoint.completed(), path.display());
+            }
+            Some(checkpoint)
+        }
+        None => None,
+    };
+    let seed = match &checkpoint {
+        Some(checkpoint) => checkpoint.seed(),
+        None => config.seed.unwrap_or_else(rand::random),
+    };
+    let checkpoint = Mutex::new(checkpoint);
+
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(config.threads)
+        .build()
+        .map_err(io::Error::other)?;
+
+    // ordered by n, then rep, then structure, which is also the order results are merged in
+    let jobs = config.sizes.iter()
+        .flat_map(|n| (0..config.reps).flat_map(move |rep| {
+            config.structures.iter().map(move |&structure| Job { structure, n, rep })
+        }))
+        .collect::<Vec<_>>();
+
+    let done = |job: &Job| {
+        checkpoint.lock().unwrap().as_ref().is_some_and(|c| c.contains(job.structure, job.n, job.rep))
+    };
+
+    // warm-up repetitions use their own seeds and are thrown away, sizes already finished skip them
+    let warmup = config.sizes.iter()
+        .filter(|&n| jobs.iter().any(|job| job.n == n && !done(job)))
+        .flat_map(|n| (config.reps..config.reps + config.warmup).flat_map(move |rep| {
+            config.structures.iter().map(move |&structure| Job { structure, n, rep })
+        }))
+        .collect::<Vec<_>>();
+    pool.install(|| warmup.par_iter().for_each(|job| { job.run(config, seed); }));
+
+    let cells = pool.install(|| jobs.par_iter().map(|job| {
+        let finished = checkpoint.lock().unwrap().as_mut().and_then(|c| c.take(job.structure, job.n, job.rep));
+        if let Some(cell) = finished {
+            return Ok(cell);
+        }
+
+        let (insert, delete) = job.run(config, seed);
+        if let Some(checkpoint) = checkpoint.lock().unwrap().as_mut() {
+            checkpoint.record(job.structure, job.n, job.rep, &insert, &delete)?;
+        }
+        println!("done {} n = {} rep = {}", job.structure, job.n, job.rep);
+        Ok((insert, delete))
+    }).collect::<io::Result<Vec<_>>>())?;
+
+    let mut results = config.structures.iter().map(|&structure| Results {
+        structure,
+        sizes: config.sizes.iter().collect(),
+        insert: config.sizes.iter().map(|_| Data::new()).collect(),
+        delete: config.sizes.iter().map(|_| Data::new()).collect(),
+    }).collect::<Vec<_>>();
+
+    for (job, (insert, delete)) in jobs.iter().zip(cells) {
+        let results = results.iter_mut().find(|r| r.structure == job.structure).unwrap();
+        let i = results.sizes.iter().position(|&n| n == job.n).unwrap();
+        results.insert[i] = std::mem::take(&mut results.insert[i]) + insert;
+        results.delete[i] = std::mem::take(&mut results.delete[i]) + delete;
+    }
+
+    Ok(Outcome { seed, results })
+}
+
+// what to measure on top of the operation counts
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Measure {
+    // walks the whole tree after every operation
+    pub height: bool,
+    // per-operation and per-phase wall-clock time
+    pub timing: bool,
+}
+
+// one repetition: a fresh tree, all inserts and then all deletes
+pub fn run_cell(structure: StructureKind, sequence: &Sequence, measure: Measure) -> (Data, Data) {
+    let mut tree = structure.build();
+    let insert_data = run_phase(tree.as_mut(), &sequence.inserts, measure, |tree, x, stat| tree.insert(x, stat));
+    let delete_data = run_phase(tree.as_mut(), &sequence.deletes, measure, |tree, x, stat| tree.delete(x, stat));
+    (insert_data, delete_data)
+}
+
+fn run_phase(
+    tree: &mut dyn Structure,
+    keys: &[usize],
+    measure: Measure,
+    op: impl Fn(&mut dyn Structure, usize, &mut Stats),
+) -> Data {
+    let mut data = Data::new();
+    // the phase time covers the whole loop, per-operation clocks included, but not the height walks
+    let mut walks = Duration::ZERO;
+    let phase = Instant::now();
+
+    for &x in keys {
+        let mut stat = Stats::new();
+        if measure.timing {
+            let start = Instant::now();
+            op(tree, x, &mut stat);
+            stat.time(start.elapsed());
+        } else {
+            op(tree, x, &mut stat);
+        }
+
+        if measure.height {
+            let start = Instant::now();
+            if let Some(height) = tree.height() {
+                stat.height(height);
+            }
+            walks += start.elapsed();
+        }
+        data.add_stat(stat);
+    }
+
+    if measure.timing {
+        data.add_phase(phase.elapsed().saturating_sub(walks));
+    }
+    data
+}
+
+pub fn series(data: &[Data], metrics: &[Metric]) -> Vec<Vec<f64>> {
+    metrics.iter()
+        .map(|metric| data.iter().map(|d| metric.value(d)).collect())
+        .collect()
+}
+
+// averages are shaded up to their maximum, phase times get ±1 standard deviation over the repetitions
+pub fn metric_series<'a>(name: &'a str, metric: Metric, data: &[Data], bands: bool) -> Series<'a> {
+    let values = data.iter().map(|d| metric.value(d)).collect::<Vec<_>>();
+    let band = match (bands, metric.max_counterpart()) {
+        (false, _) => None,
+        (true, Some(max)) => Some(Band { low: values.clone(), high: data.iter().map(|d| max.value(d)).collect() }),
+        (true, None) if metric == Metric::PhaseNanos => {
+            let samples = data.iter().map(Data::phase_nanos_samples).collect::<Vec<_>>();
+            // a single repetition has no spread to show
+            samples.iter().all(|s| s.len() > 1).then(|| Band::stddev(&samples).1)
+        }
+        (true, None) => None,
+    };
+    Series { name, values, band }
+}
+
+// one chart per structure and phase, with a line for every metric
+pub fn draw_structure_charts(name: &str, results: &[Results], metrics: &[Metric], options: &ChartOptions) -> Result<(), ChartError> {
+    for results in results {
+        for (phase, data) in [("insert", &results.insert), ("delete", &results.delete)] {
+            let chart = format!("{}_{}_{}", name, results.structure, phase);
+            let lines = metrics.iter()
+                .map(|&metric| metric_series(metric.name(), metric, data, options.wants_bands()))
+                .collect();
+            draw_series(lines, results.sizes.iter().copied(), &chart, options)?;
+        }
+    }
+    Ok(())
+}
+
+// one chart per phase and metric, with a line for every structure
+pub fn draw_comparison(name: &str, results: &[Results], metrics: &[Metric], options: &ChartOptions) -> Result<(), ChartError> {
+    let Some(first) = results.first() else { return Ok(()) };
+
+    for phase in ["insert", "delete"] {
+        for metric in metrics {
+            // the library structures have no height or depths to show
+            let shown = results.iter()
+                .filter(|r| !metric.tree_only() || r.structure.is_tree())
+                .collect::<Vec<_>>();
+            if shown.is_empty() {
+                continue;
+            }
+            let lines = shown.iter()
+                .map(|r| {
+                    let data = if phase == "insert" { &r.insert } else { &r.delete };
+                    metric_series(r.structure.name(), *metric, data, options.wants_bands())
+                })
+                .collect();
+            let chart = format!("{}_{}_{}", name, phase, metric.key());
+            let options = options.clone().y_label(metric.name());
+            draw_series(lines, first.sizes.iter().copied(), &chart, &options)?;
+        }
+    }
+    Ok(())
+}
+
+// results of a different experiment under the same name are never written over; the seed
+// only counts when the config fixes it, and the revision is expected to move between runs
+pub fn check_overwrite(path: &Path, config: &Config, saved: &Saved) -> io::Result<()> {
+    if !path.exists() {
+        return Ok(());
+    }
+    let refuse = |msg: String| io::Error::new(io::ErrorKind::AlreadyExists, format!("{}: {}, not overwriting it", path.display(), msg));
+    let old = Saved::load(path).map_err(|e| refuse(format!("cannot read the results already there ({})", e)))?;
+    let structures = |saved: &Saved| saved.results.iter().map(|r| r.structure.name()).collect::<Vec<_>>().join(" ");
+    let (old_meta, new_meta) = (&old.metadata, &saved.metadata);
+    let mut compared = vec![
+        ("name", old_meta.name.clone(), new_meta.name.clone()),
+        ("workload", old_meta.workload.to_string(), new_meta.workload.to_string()),
+        ("reps", old_meta.reps.to_string(), new_meta.reps.to_string()),
+        ("structures", structures(&old), structures(saved)),
+    ];
+    if config.seed.is_some() {
+        compared.push(("seed", old_meta.seed.to_string(), new_meta.seed.to_string()));
+    }
+    match compared.into_iter().find(|(_, old, new)| old != new) {
+        Some((key, old, new)) => Err(refuse(format!("it holds {} `{}`, this run has `{}`", key, old, new))),
+        None => Ok(()),
+    }
+}
+
+pub fn write_outputs(config: &Config, outcome: &Outcome) -> io::Result<()> {
+    let outputs = &config.outputs;
+
+    if outputs.csv || outputs.json {
+        fs::create_dir_all(&outputs.dir)?;
+        let saved = Saved::new(config, outcome);
+        if outputs.csv {
+            let path = outputs.dir.join(format!("{}.csv", config.name));
+            check_overwrite(&path, config, &saved)?;
+            saved.save_csv(&path)?;
+        }
+        if outputs.json {
+            let path = outputs.dir.join(format!("{}.json", config.name));
+            check_overwrite(&path, config, &saved)?;
+            saved.save_json(&path)?;
+        }
+    }
+
+    let options = ChartOptions::new()
+        .dir(&outputs.dir)
+        .format(outputs.format)
+        .x_label("n")
+        .log_x(outputs.log_x)
+        .log_y(outputs.log_y)
+        .normalize(outputs.normalize)
+        .bands(outputs.bands);
+    if outputs.comparison {
+        draw_comparison(&config.name, &outcome.results, &config.metrics, &options).map_err(io::Error::other)?;
+    }
+    if outputs.charts {
+        draw_structure_charts(&config.name, &outcome.results, &config.metrics, &options).map_err(io::Error::other)?;
+    }
+    if outputs.report {
+        write_report(&Saved::new(config, outcome), &config.metrics, &options)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,190 @@
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+
+// read-only handle on a node, enough to walk any of the trees without knowing its node type
+pub trait NodeView: Sized {
+    fn left(&self) -> Option<Self>;
+    fn right(&self) -> Option<Self>;
+
+    // Some(true) for a red node, None for trees without colors
+    fn red(&self) -> Option<bool> {
+        None
+    }
+
+    // tells nodes apart, the address of the node
+    fn id(&self) -> usize;
+
+    // None for trees without parent pointers, Some(None) when the pointer is unset or its node is gone
+    fn parent(&self) -> Option<Option<Self>> {
+        None
+    }
+}
+
+// number of nodes at each depth, the root being at depth 0
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DepthHistogram {
+    pub counts: Vec<usize>,
+}
+
+impl DepthHistogram {
+    // explicit stack, a degenerate tree is as deep as it is large
+    pub fn of<N: NodeView>(root: Option<N>) -> Self {
+        let mut counts = Vec::new();
+        let mut stack = root.into_iter().map(|node| (node, 0)).collect::<Vec<_>>();
+        while let Some((node, depth)) = stack.pop() {
+            if counts.len() <= depth {
+                counts.resize(depth + 1, 0);
+            }
+            counts[depth] += 1;
+            stack.extend(node.left().map(|left| (left, depth + 1)));
+            stack.extend(node.right().map(|right| (right, depth + 1)));
+        }
+        DepthHistogram { counts }
+    }
+
+    pub fn size(&self) -> usize {
+        self.counts.iter().sum()
+    }
+
+    // sum of the depths of all nodes
+    pub fn internal_path_length(&self) -> usize {
+        self.counts.iter().enumerate().map(|(depth, count)| depth * count).sum()
+    }
+
+    pub fn avg_depth(&self) -> f64 {
+        if self.counts.is_empty() {
+            return 0.0;
+        }
+        self.internal_path_length() as f64 / self.size() as f64
+    }
+}
+
+impl Display for DepthHistogram {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        let widest = self.counts.iter().copied().max().unwrap_or(0).max(1);
+        for (depth, &count) in self.counts.iter().enumerate() {
+            let bar = (count * 50).div_ceil(widest);
+            writeln!(f, "{:>5} {:>8} {}", depth, count, "#".repeat(bar))?;
+        }
+        write!(f, "nodes: {}, internal path length: {}, avg depth: {:.3}",
+               self.size(), self.internal_path_length(), self.avg_depth())
+    }
+}
+
+// everything that explains how a tree's shape drives its costs, heights counted in nodes
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Shape {
+    pub size: usize,
+    pub height: usize,
+    // depth of the shallowest node without children
+    pub min_leaf_depth: usize,
+    pub depths: DepthHistogram,
+    // sum of the depths of the n + 1 empty child links
+    pub external_path_length: usize,
+    // height of the right subtree minus height of the left one, node count for each value
+    pub balance_factors: BTreeMap<isize, usize>,
+    // black nodes on every path from the root down, None for uncolored trees or when the paths disagree
+    pub black_height: Option<usize>,
+}
+
+enum Visit<N> {
+    Enter(N, usize),
+    // a node whose children are done, their (height, black height) results are on the result stack
+    Exit { children: (bool, bool), red: Option<bool> },
+}
+
+impl Shape {
+    // one pass, post-order on an explicit stack
+    pub fn of<N: NodeView>(root: Option<N>) -> Self {
+        let mut shape = Shape::default();
+        let colored = root.as_ref().is_some_and(|root| root.red().is_some());
+        let mut min_leaf_depth = None;
+        let mut results: Vec<(usize, Option<usize>)> = Vec::new();
+        let mut stack = root.into_iter().map(|node| Visit::Enter(node, 0)).collect::<Vec<_>>();
+
+        while let Some(visit) = stack.pop() {
+            match visit {
+                Visit::Enter(node, depth) => {
+                    shape.size += 1;
+                    if shape.depths.counts.len() <= depth {
+                        shape.depths.counts.resize(depth + 1, 0);
+                    }
+                    shape.depths.counts[depth] += 1;
+
+                    let (left, right) = (node.left(), node.right());
+                    let missing = left.is_none() as usize + right.is_none() as usize;
+                    shape.external_path_length += missing * (depth + 1);
+                    if missing == 2 && min_leaf_depth.is_none_or(|min| depth < min) {
+                        min_leaf_depth = Some(depth);
+                    }
+
+                    stack.push(Visit::Exit { children: (left.is_some(), right.is_some()), red: node.red() });
+                    stack.extend(right.map(|right| Visit::Enter(right, depth + 1)));
+                    stack.extend(left.map(|left| Visit::Enter(left, depth + 1)));
+                }
+                Visit::Exit { children: (has_left, has_right), red } => {
+                    // the left child finished first, so its result sits below the right one
+                    let empty = (0, Some(0));
+                    let right = if has_right { results.pop().unwrap() } else { empty };
+                    let left = if has_left { results.pop().unwrap() } else { empty };
+
+                    *shape.balance_factors.entry(right.0 as isize - left.0 as isize).or_insert(0) += 1;
+                    let black_height = match (left.1, right.1) {
+                        (Some(l), Some(r)) if l == r => Some(l + (red == Some(false)) as usize),
+                        _ => None,
+                    };
+                    results.push((1 + left.0.max(right.0), black_height));
+                }
+            }
+        }
+
+        if let Some((height, black_height)) = results.pop() {
+            shape.height = height;
+            shape.black_height = if colored { black_height } else { None };
+        }
+        shape.min_leaf_depth = min_leaf_depth.unwrap_or(0);
+        shape
+    }
+
+    pub fn avg_depth(&self) -> f64 {
+        self.depths.avg_depth()
+    }
+
+    pub fn internal_path_length(&self) -> usize {
+        self.depths.internal_path_length()
+    }
+
+    // 1 for a perfect tree, 2 is the red-black worst case
+    pub fn height_ratio(&self) -> f64 {
+        if self.size == 0 {
+            return 0.0;
+        }
+        self.height as f64 / (self.size as f64 + 1.0).log2()
+    }
+
+    // share of nodes whose subtrees differ in height by at most one
+    pub fn avl_balanced(&self) -> f64 {
+        if self.size == 0 {
+            return 1.0;
+        }
+        let balanced = (-1..=1).filter_map(|bf| self.balance_factors.get(&bf)).sum::<usize>();
+        balanced as f64 / self.size as f64
+    }
+}
+
+impl Display for Shape {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        writeln!(f, "size: {}, height: {}, min leaf depth: {}", self.size, self.height, self.min_leaf_depth)?;
+        writeln!(f, "avg depth: {:.3}, height / log2(n+1): {:.3}", self.avg_depth(), self.height_ratio())?;
+        writeln!(f, "internal path length: {}, external path length: {}",
+                 self.internal_path_length(), self.external_path_length)?;
+        if let Some(black_height) = self.black_height {
+            writeln!(f, "black height: {}", black_height)?;
+        }
+        write!(f, "balance factors:")?;
+        for (bf, count) in &self.balance_factors {
+            write!(f, " {}:{}", bf, count)?;
+        }
+        Ok(())
+    }
+}
@@ -1,13 +1,60 @@
 mod tree_node;
 
 use std::fmt::{Debug, Formatter};
+use std::io;
+
 use itertools::Itertools;
+
+use crate::dot::{to_dot, DotOptions};
 use crate::experiment::Stats;
+use crate::frames::{debug_key, Keys, Snapshot};
+use crate::printer::{print_tree, write_tree, PrintOptions};
+use crate::shape::{DepthHistogram, NodeView, Shape};
+use crate::validate::{validate, InvariantError};
 use crate::splay_tree::tree_node::SplayNode;
 
 #[derive(Clone)]
 pub struct SplayTree<T: Ord + Clone> {
     root: Option<Box<SplayNode<T>>>,
+    // Sleator-Tarjan accounting of the last operation, only kept by trees made with `tracked`
+    track: bool,
+    last: Option<Step>,
+}
+
+// one operation in the potential method: amortized cost = rotations + change of potential
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Step {
+    pub rotations: usize,
+    pub potential_change: f64,
+}
+
+impl Step {
+    pub fn amortized(&self) -> f64 {
+        self.rotations as f64 + self.potential_change
+    }
+}
+
+pub struct Iter<'a, T: Ord + Clone> {
+    stack: Vec<&'a SplayNode<T>>,
+}
+
+impl<'a, T: Ord + Clone> Iter<'a, T> {
+    fn push_left(&mut self, mut node: Option<&'a SplayNode<T>>) {
+        while let Some(current) = node {
+            self.stack.push(current);
+            node = current.left();
+        }
+    }
+}
+
+impl<'a, T: Ord + Clone> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        let node = self.stack.pop()?;
+        self.push_left(node.right());
+        Some(node.value())
+    }
 }
 
 impl<T: Ord + Clone> Default for SplayTree<T> {
@@ -18,14 +65,49 @@ impl<T: Ord + Clone> Default for SplayTree<T> {
 
 impl<T: Ord + Clone> SplayTree<T> {
     pub fn new() -> Self {
-        SplayTree { root: None }
+        SplayTree { root: None, track: false, last: None }
+    }
+
+    // walks the whole tree before and after every operation to measure the potential
+    pub fn tracked() -> Self {
+        SplayTree { root: None, track: true, last: None }
+    }
+
+    // sum of log2(subtree size) over all nodes
+    pub fn potential(&self) -> f64 {
+        self.root.as_ref().map_or(0.0, |node| node.potential().1)
+    }
+
+    pub fn last_step(&self) -> Option<Step> {
+        self.last
+    }
+
+    fn track(&mut self, stats: &mut Stats, op: impl FnOnce(&mut Self, &mut Stats)) {
+        if !self.track {
+            return op(self, stats);
+        }
+        let before = self.potential();
+        let rotations = stats.rotations();
+        op(self, stats);
+        self.last = Some(Step { rotations: stats.rotations() - rotations, potential_change: self.potential() - before });
     }
 
     pub fn insert(&mut self, value: T, stats: &mut Stats) {
+        self.insert_keyed(value, stats, None);
+    }
+
+    fn insert_keyed(&mut self, value: T, stats: &mut Stats, keys: Keys<T>) {
+        self.track(stats, |tree, stats| tree.insert_untracked(value, stats, keys));
+        #[cfg(feature = "check-invariants")]
+        self.check("insert", keys);
+    }
+
+    fn insert_untracked(&mut self, value: T, stats: &mut Stats, keys: Keys<T>) {
         if let Some(root) = self.root.take() {
-            self.root = Some(root.insert(value, stats));
+            self.root = Some(root.insert(value, 0, keys, stats));
         } else {
             self.root = Some(Box::new(SplayNode::new(value)));
+            SplayNode::frame(stats, keys, self.root.as_deref(), |_| "new root".to_string());
         }
     }
 
@@ -33,26 +115,236 @@ impl<T: Ord + Clone> SplayTree<T> {
         self.root.as_ref().map_or(0, |node| node.height())
     }
 
+    pub fn depths(&self) -> DepthHistogram {
+        DepthHistogram::of(self.root.as_deref())
+    }
+
+    pub fn shape(&self) -> Shape {
+        Shape::of(self.root.as_deref())
+    }
+
     // pub fn print_tree(&self) {
     //     if let Some(root) = self.root.as_ref() {
     //         root.print_tree_helper("", false);
     //     }
     // }
 
-    pub fn delete(&mut self, value: T, stats: &mut Stats) {
+    pub fn delete(&mut self, value: T, stats: &mut Stats) -> bool {
+        self.delete_keyed(value, stats, None)
+    }
+
+    fn delete_keyed(&mut self, value: T, stats: &mut Stats, keys: Keys<T>) -> bool {
+        let mut found = false;
+        self.track(stats, |tree, stats| found = tree.delete_untracked(value, stats, keys));
+        #[cfg(feature = "check-invariants")]
+        self.check("delete", keys);
+        found
+    }
+
+    fn delete_untracked(&mut self, value: T, stats: &mut Stats, keys: Keys<T>) -> bool {
+        let Some(root) = self.root.take() else { return false };
+        let (root, found) = root.delete(value, keys, stats);
+        self.root = root;
+        found
+    }
+
+    // ordering only; keys are written by keys when there is one, otherwise nodes go by their address
+    #[cfg(feature = "check-invariants")]
+    fn check(&self, op: &str, keys: Keys<T>) {
+        let key = |node: &&SplayNode<T>| match keys {
+            Some(keys) => keys(node.value()),
+            None => format!("node at {:#x}", node.id()),
+        };
+        if let Err(e) = crate::validate::validate_with(self.root.as_deref(), None, None, key) {
+            panic!("{} left the tree broken: {}", op, e);
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    // one node at a time, a splay tree can be a single long path and dropping that recurses down all of it
+    pub fn clear(&mut self) {
+        let mut stack: Vec<Box<SplayNode<T>>> = self.root.take().into_iter().collect();
+        while let Some(mut node) = stack.pop() {
+            stack.extend(node.take_children().into_iter().flatten());
+        }
+        self.last = None;
+    }
+
+    // balanced in O(n) with nothing splayed, the keys have to come in order, repeats allowed
+    pub fn from_sorted_iter(iter: impl IntoIterator<Item = T>) -> Self {
+        let nodes = iter.into_iter().map(|value| Box::new(SplayNode::new(value))).collect::<Vec<_>>();
+        assert!(nodes.is_sorted_by(|a, b| a.value() <= b.value()), "from_sorted_iter got keys out of order");
+        let mut tree = Self::new();
+        tree.rebuild(nodes);
+        tree
+    }
+
+    fn rebuild(&mut self, nodes: Vec<Box<SplayNode<T>>>) {
+        let size = nodes.len();
+        self.root = SplayNode::build(&mut nodes.into_iter(), size);
+        self.last = None;
+        #[cfg(feature = "check-invariants")]
+        self.check("from_sorted_iter", None);
+    }
+
+    // every key of left no greater than any of right, amortized O(log n)
+    pub fn join(left: Self, right: Self) -> Self {
+        let track = left.track;
+        let root = match (left.root, right.root) {
+            (Some(left), Some(right)) => Some(SplayNode::join(left, right, &mut Stats::new())),
+            (left, right) => left.or(right),
+        };
+        let tree = SplayTree { root, track, last: None };
+        #[cfg(feature = "check-invariants")]
+        tree.check("join", None);
+        tree
+    }
+
+    // the keys from key up move into the returned tree, amortized O(log n)
+    pub fn split_off(&mut self, key: &T) -> Self {
+        let mut right = SplayTree { root: None, track: self.track, last: None };
+        self.last = None;
         if let Some(root) = self.root.take() {
-            self.root = root.delete(value, stats);
+            (self.root, right.root) = root.split(key, &mut Stats::new());
         }
+        #[cfg(feature = "check-invariants")]
+        {
+            self.check("split_off", None);
+            right.check("split_off", None);
+        }
+        right
+    }
+
+    // moves every key of other into self, leaving it empty; amortized O(log n) when all the keys of
+    // one tree are no greater than all of the other's, otherwise the two are merged and rebuilt in O(n)
+    pub fn append(&mut self, other: &mut Self) {
+        self.last = None;
+        let (mine, theirs) = match (self.root.take(), other.root.take()) {
+            (Some(mine), Some(theirs)) => (mine, theirs),
+            (mine, theirs) => {
+                self.root = mine.or(theirs);
+                return;
+            }
+        };
+        // the ends the joins need are splayed up, which orders the two in a comparison or two
+        let stats = &mut Stats::new();
+        let (mine, theirs) = (mine.splay_max(stats), theirs.splay_min(stats));
+        if mine.value() <= theirs.value() {
+            self.root = Some(mine.hang(theirs, stats));
+        } else {
+            let (mine, theirs) = (mine.splay_min(stats), theirs.splay_max(stats));
+            if theirs.value() <= mine.value() {
+                self.root = Some(theirs.hang(mine, stats));
+            } else {
+                let mut other = SplayTree { root: Some(theirs), track: false, last: None };
+                self.root = Some(mine);
+                let nodes = self.take_nodes().into_iter().merge_by(other.take_nodes(), |a, b| a.value() <= b.value()).collect();
+                self.rebuild(nodes);
+            }
+        }
+        #[cfg(feature = "check-invariants")]
+        self.check("append", None);
+    }
+
+    // the nodes in order, unlinked from each other, leaving the tree empty
+    fn take_nodes(&mut self) -> Vec<Box<SplayNode<T>>> {
+        let mut nodes = Vec::new();
+        let mut stack = Vec::new();
+        let mut current = self.root.take();
+        loop {
+            while let Some(mut node) = current {
+                let [left, right] = node.take_children();
+                current = left;
+                stack.push((node, right));
+            }
+            let Some((node, right)) = stack.pop() else { break };
+            current = right;
+            nodes.push(node);
+        }
+        nodes
+    }
+
+    // a plain search that leaves the tree as it is, nothing is splayed or counted
+    pub fn contains(&self, value: &T) -> bool {
+        let mut current = self.root.as_deref();
+        while let Some(node) = current {
+            current = match value.cmp(node.value()) {
+                std::cmp::Ordering::Less => node.left(),
+                std::cmp::Ordering::Greater => node.right(),
+                std::cmp::Ordering::Equal => return true,
+            };
+        }
+        false
+    }
+
+    // the keys in order
+    pub fn iter(&self) -> Iter<'_, T> {
+        let mut iter = Iter { stack: Vec::new() };
+        iter.push_left(self.root.as_deref());
+        iter
     }
 }
 
-impl<T: Debug + Ord + std::clone::Clone> Debug for SplayTree<T> {
-    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
-        if let Some(root) = &self.root {
-            root.print_node(f, String::new());
+impl<T: Ord + Clone + Debug> SplayTree<T> {
+    // insert and delete that record a frame of each step when stats is recording
+    pub fn insert_framed(&mut self, value: T, stats: &mut Stats) {
+        self.insert_keyed(value, stats, Some(debug_key));
+    }
+
+    pub fn delete_framed(&mut self, value: T, stats: &mut Stats) -> bool {
+        self.delete_keyed(value, stats, Some(debug_key))
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot::of(self.root.as_deref())
+    }
+
+    // graphviz source of the tree, render it with `dot -Tsvg`
+    pub fn to_dot(&self, options: &DotOptions) -> String {
+        to_dot(self.root.as_deref(), options)
+    }
+
+    // ordering only, the nodes keep no parents and the tree no count
+    pub fn validate(&self) -> Result<(), InvariantError> {
+        validate(self.root.as_deref(), None, None)
+    }
+
+    // drawn as text, see PrintOptions for the layouts and limits
+    pub fn print(&self, out: &mut impl io::Write, options: &PrintOptions) -> io::Result<()> {
+        print_tree(out, self.root.as_deref(), options)
+    }
+}
+
+// sorted input at least as long as the tree is merged into it in one pass and the whole
+// tree rebuilt, anything else goes in one insert at a time; a tracked tree keeps tracking
+impl<T: Ord + Clone> Extend<T> for SplayTree<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let values = iter.into_iter().collect::<Vec<_>>();
+        // the tree keeps no count, walking no further than values is enough to compare
+        if values.is_sorted() && self.iter().nth(values.len()).is_none() {
+            let new = values.into_iter().map(|value| Box::new(SplayNode::new(value)));
+            let nodes = self.take_nodes().into_iter().merge_by(new, |a, b| a.value() <= b.value()).collect();
+            self.rebuild(nodes);
         } else {
-            write!(f, "Empty tree")?;
+            let stats = &mut Stats::new();
+            values.into_iter().for_each(|value| self.insert(value, stats));
         }
-        Ok(())
+    }
+}
+
+impl<T: Ord + Clone> FromIterator<T> for SplayTree<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut tree = Self::new();
+        tree.extend(iter);
+        tree
+    }
+}
+
+impl<T: Debug + Ord + std::clone::Clone> Debug for SplayTree<T> {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write_tree(f, self.root.as_deref(), &PrintOptions::default())
     }
 }
\ No newline at end of file
@@ -1,12 +1,20 @@
-use std::fmt::{Debug, Formatter};
-use itertools::Itertools;
+use std::cmp::Ordering;
+use std::fmt::Debug;
 use crate::experiment::Stats;
+use crate::dot::DotNode;
+use crate::frames::{Keys, Side, Snapshot};
+use crate::live::Live;
+use crate::shape::NodeView;
+use crate::structure::StructureKind;
+use crate::validate::CheckedNode;
 
 #[derive(Debug, Clone)]
 pub(super) struct SplayNode<T: Ord + Clone> {
     value: T,
     left: Option<Box<SplayNode<T>>>,
     right: Option<Box<SplayNode<T>>>,
+    // held for its drop
+    _live: Live,
 }
 
 
@@ -16,6 +24,7 @@ impl<T: Ord + Clone> SplayNode<T> {
             value,
             left: None,
             right: None,
+            _live: Live::new(StructureKind::SplayTree),
         }
     }
 
@@ -23,6 +32,7 @@ impl<T: Ord + Clone> SplayNode<T> {
     fn rotate_right(mut self: Box<Self>, stats: &mut Stats) -> Box<Self> {
         stats.read();
         if self.left.is_some() {
+            stats.rotate();
             stats.swap();
             let mut x = self.left.take().unwrap();
             stats.swap();
@@ -37,6 +47,7 @@ impl<T: Ord + Clone> SplayNode<T> {
     fn rotate_left(mut self: Box<Self>, stats: &mut Stats) -> Box<Self> {
         stats.read();
         if self.right.is_some() {
+            stats.rotate();
             stats.swap();
             let mut x = self.right.take().unwrap();
             stats.swap();
@@ -47,97 +58,259 @@ impl<T: Ord + Clone> SplayNode<T> {
         self
     }
 
+    fn link(&mut self, path: &[Side]) -> &mut Option<Box<Self>> {
+        let link = match path[0] {
+            Side::Left => &mut self.left,
+            Side::Right => &mut self.right,
+        };
+        match &path[1..] {
+            [] => link,
+            rest => link.as_mut().unwrap().link(rest),
+        }
+    }
+
+    // a frame of tree after a step, when there is a way to write the keys
+    pub(super) fn frame(stats: &mut Stats, keys: Keys<T>, tree: Option<&Self>, caption: impl FnOnce(fn(&T) -> String) -> String) {
+        if let Some(keys) = keys {
+            stats.frame(|| caption(keys), || Snapshot::of_with(tree, |node| keys(&node.value)));
+        }
+    }
+
+    // runs op on the subtree at path, if there is one, and hangs what it returns back in its place;
+    // while it is detached the recorder keeps the rest of the tree
+    fn descend(&mut self, path: &[Side], keys: Keys<T>, stats: &mut Stats, op: impl FnOnce(Box<Self>, &mut Stats) -> Box<Self>) {
+        let Some(subtree) = self.link(path).take() else { return };
+        if let Some(keys) = keys {
+            stats.enter(|| (Snapshot::of_with(Some(&*self), |node| keys(&node.value)), path.to_vec()));
+        }
+        let subtree = op(subtree, stats);
+        if keys.is_some() {
+            stats.leave();
+        }
+        *self.link(path) = Some(subtree);
+    }
+
     // Perform a splay operation on the node.
-    fn splay(mut self: Box<Self>, value: T, stats: &mut Stats) -> Box<Self> {
+    // depth is that of the node when the splay is the search of a delete, the depth where the
+    // search ends is then recorded on the way down; the other splays pass None. Frames of the
+    // steps are recorded when keys gives a way to write the keys
+    fn splay(mut self: Box<Self>, value: T, depth: Option<usize>, keys: Keys<T>, stats: &mut Stats) -> Box<Self> {
+        let reach = |stats: &mut Stats, below: usize| if let Some(depth) = depth {
+            stats.depth(depth + below);
+        };
+        let deeper = depth.map(|depth| depth + 2);
+        // rotations made at this level, the ones below are counted from after descend returns
+        let mut rotations = stats.rotations();
+        let mut steps = ("", "");
         stats.comp();
         if value < self.value {
             stats.read();
             if let Some(ref mut left) = self.left {
+                steps.0 = "zig: right-rotate parent";
                 stats.comp();
                 if value < left.value {
                     // Zig-Zig
+                    if left.left.is_none() {
+                        reach(stats, 1);
+                    }
                     stats.swap();
-                    left.left = left.left.take().map(|node| node.splay(value, stats));
+                    self.descend(&[Side::Left, Side::Left], keys, stats, |node, stats| node.splay(value, deeper, keys, stats));
+                    rotations = stats.rotations();
                     self = self.rotate_right(stats);
+                    steps.1 = "zig-zig: right-rotate grandparent, then parent";
                 } else if value > left.value {
                     // Zig-Zag4
+                    if left.right.is_none() {
+                        reach(stats, 1);
+                    }
                     stats.swap();
-                    left.right = left.right.take().map(|node| node.splay(value, stats));
+                    self.descend(&[Side::Left, Side::Right], keys, stats, |node, stats| node.splay(value, deeper, keys, stats));
+                    rotations = stats.rotations();
                     self.left = self.left.map(|node| node.rotate_left(stats));
+                    steps.1 = "zig-zag: left-rotate parent, then right-rotate grandparent";
+                } else {
+                    reach(stats, 1);
                 }
                 if let Some(ref mut _left) = self.left {
                     stats.read();
                     self = self.rotate_right(stats);
                 }
+            } else {
+                reach(stats, 0);
             }
         } else if value > self.value {
             if let Some(ref mut right) = self.right {
+                steps.0 = "zig: left-rotate parent";
                 stats.comp();
                 if value > right.value {
                     // Zag-Zag
+                    if right.right.is_none() {
+                        reach(stats, 1);
+                    }
                     stats.swap();
-                    right.right = right.right.take().map(|node| node.splay(value, stats));
+                    self.descend(&[Side::Right, Side::Right], keys, stats, |node, stats| node.splay(value, deeper, keys, stats));
+                    rotations = stats.rotations();
                     self = self.rotate_left(stats);
+                    steps.1 = "zig-zig: left-rotate grandparent, then parent";
                 } else if value < right.value {
                     // Zag-Zig
+                    if right.left.is_none() {
+                        reach(stats, 1);
+                    }
                     stats.swap();
-                    right.left = right.left.take().map(|node| node.splay(value, stats));
+                    self.descend(&[Side::Right, Side::Left], keys, stats, |node, stats| node.splay(value, deeper, keys, stats));
+                    rotations = stats.rotations();
                     self.right = self.right.map(|node| node.rotate_right(stats));
+                    steps.1 = "zig-zag: right-rotate parent, then left-rotate grandparent";
+                } else {
+                    reach(stats, 1);
                 }
                 if let Some(ref mut _right) = self.right {
                     stats.read();
                     self = self.rotate_left(stats);
                 }
+            } else {
+                reach(stats, 0);
             }
+        } else {
+            reach(stats, 0);
+        }
+        // a key that isn't there can leave a zig-zig or zig-zag one rotation short
+        let step = match stats.rotations() - rotations {
+            0 => None,
+            1 => Some(steps.0),
+            _ => Some(steps.1),
+        };
+        if let Some(step) = step {
+            Self::frame(stats, keys, Some(&*self), |keys| format!("splay {}, {}", keys(&self.value), step));
         }
         self
     }
 
-    // Insert a value into the splay tree.
-    pub(crate) fn insert(mut self: Box<Self>, value: T, stats: &mut Stats) -> Box<Self> {
+    // Insert a value into the splay tree, depth is that of the node and the new leaf's is recorded.
+    pub(crate) fn insert(mut self: Box<Self>, value: T, depth: usize, keys: Keys<T>, stats: &mut Stats) -> Box<Self> {
         stats.comp();
         if value < self.value {
             stats.read();
-            if let Some(left) = self.left.take() {
+            if self.left.is_some() {
                 stats.swap();
-                self.left = Some(left.insert(value.clone(), stats));
+                self.descend(&[Side::Left], keys, stats, |left, stats| left.insert(value.clone(), depth + 1, keys, stats));
             } else {
                 stats.swap();
                 self.left = Some(Box::new(SplayNode::new(value.clone())));
+                stats.depth(depth + 1);
+                Self::frame(stats, keys, Some(&*self), |_| "new leaf".to_string());
             }
         } else if value >= self.value {
             stats.read();
-            if let Some(right) = self.right.take() {
+            if self.right.is_some() {
                 stats.swap();
-                self.right = Some(right.insert(value.clone(), stats));
+                self.descend(&[Side::Right], keys, stats, |right, stats| right.insert(value.clone(), depth + 1, keys, stats));
             } else {
                 stats.swap();
                 self.right = Some(Box::new(SplayNode::new(value.clone())));
+                stats.depth(depth + 1);
+                Self::frame(stats, keys, Some(&*self), |_| "new leaf".to_string());
             }
         }
-        self.splay(value, stats)
+        self.splay(value, None, keys, stats)
     }
 
-    // Perform a delete operation on the node.
-    pub(crate) fn delete(mut self: Box<Self>, value: T, stats: &mut Stats) -> Option<Box<Self>> {
-        self = self.splay(value.clone(), stats);
+    // Perform a delete operation on the node, the flag tells whether value was there.
+    pub(crate) fn delete(mut self: Box<Self>, value: T, keys: Keys<T>, stats: &mut Stats) -> (Option<Box<Self>>, bool) {
+        self = self.splay(value.clone(), Some(0), keys, stats);
         stats.swap();
         stats.comp();
         if value != self.value {
-            return Some(self);
+            return (Some(self), false);
         }
 
         stats.read();
         stats.read();
-        match (self.left.take(), self.right.take()) {
-            (None, right) => right,
-            (left, None) => left,
+        let (tree, step) = match (self.left.take(), self.right.take()) {
+            (None, None) => (None, "last node removed"),
+            (None, right) => (right, "root removed, its right subtree takes its place"),
+            (left, None) => (left, "root removed, its left subtree takes its place"),
             (Some(left), Some(right)) => {
-                let mut x = right.splay(value, stats);
-                x.left = Some(left);
-                Some(x)
+                // the root and its left subtree stay in the frames while the right one is splayed
+                self.left = Some(left);
+                if let Some(keys) = keys {
+                    stats.enter(|| (Snapshot::of_with(Some(&*self), |node| keys(&node.value)), vec![Side::Right]));
+                }
+                let mut x = right.splay(value, None, keys, stats);
+                if keys.is_some() {
+                    stats.leave();
+                }
+                // copies of value can be left of the one splayed up, the left subtree goes below all of them
+                let mut smallest = &mut x;
+                while smallest.left.is_some() {
+                    smallest = smallest.left.as_mut().unwrap();
+                }
+                smallest.left = self.left.take();
+                (Some(x), "root removed, left subtree hung under the smallest key on the right")
             }
+        };
+        Self::frame(stats, keys, tree.as_deref(), |keys| format!("delete {}, {}", keys(&self.value), step));
+        (tree, true)
+    }
+
+    // key splayed to the top and the tree cut next to it, the keys below key on the left
+    pub(crate) fn split(self: Box<Self>, key: &T, stats: &mut Stats) -> (Option<Box<Self>>, Option<Box<Self>>) {
+        let mut root = self.splay(key.clone(), None, None, stats);
+        stats.comp();
+        if root.value < *key {
+            let right = root.right.take();
+            return (Some(root), right);
+        }
+        let mut left = root.left.take();
+        let mut right = root;
+        stats.comp();
+        if right.value == *key {
+            // copies of key can be left of the one splayed up, they move over one splay at a time
+            while let Some(node) = left.take() {
+                let mut node = node.splay(key.clone(), None, None, stats);
+                stats.comp();
+                if node.value != *key {
+                    left = Some(node);
+                    break;
+                }
+                left = node.left.take();
+                right = Self::join(node, right, stats);
+            }
+        }
+        (left, Some(right))
+    }
+
+    // every key of left no greater than any of right; the largest key on the left and the smallest
+    // on the right are splayed up, so checking that is a single comparison of the two roots
+    pub(crate) fn join(left: Box<Self>, right: Box<Self>, stats: &mut Stats) -> Box<Self> {
+        let (left, right) = (left.splay_max(stats), right.splay_min(stats));
+        stats.comp();
+        assert!(left.value <= right.value, "join got a left tree with keys above the right one's");
+        left.hang(right, stats)
+    }
+
+    // the walk down to the largest key is the path the splay takes, it pays for it
+    pub(crate) fn splay_max(self: Box<Self>, stats: &mut Stats) -> Box<Self> {
+        let largest = self.max().clone();
+        self.splay(largest, None, None, stats)
+    }
+
+    pub(crate) fn splay_min(self: Box<Self>, stats: &mut Stats) -> Box<Self> {
+        let smallest = self.min().clone();
+        self.splay(smallest, None, None, stats)
+    }
+
+    // right goes on the right of a root that has had the largest key splayed up, no greater than any of right's
+    pub(crate) fn hang(mut self: Box<Self>, right: Box<Self>, stats: &mut Stats) -> Box<Self> {
+        // copies of the largest key can be right of the one splayed up, right goes below all of them
+        let mut last = &mut self;
+        while last.right.is_some() {
+            last = last.right.as_mut().unwrap();
         }
+        stats.swap();
+        last.right = Some(right);
+        self
     }
 
     // Get the height of the splay tree rooted at this node.
@@ -147,6 +320,14 @@ impl<T: Ord + Clone> SplayNode<T> {
         1 + std::cmp::max(left_height, right_height)
     }
 
+    // size of the subtree and the sum of log2(size) over all its subtrees
+    pub(crate) fn potential(&self) -> (usize, f64) {
+        let (left_size, left_potential) = self.left.as_ref().map_or((0, 0.0), |node| node.potential());
+        let (right_size, right_potential) = self.right.as_ref().map_or((0, 0.0), |node| node.potential());
+        let size = 1 + left_size + right_size;
+        (size, left_potential + right_potential + (size as f64).log2())
+    }
+
     // // Print the node value with the given prefix and side indication.
     // fn print_node(&self, prefix: &str, is_left: bool) {
     //     let side = if is_left { "└──" } else { "├──" };
@@ -165,31 +346,66 @@ impl<T: Ord + Clone> SplayNode<T> {
     // }
 }
 
-impl<T: Ord + Clone + Debug> SplayNode<T> {
-    pub(crate) fn print_node(&self, f: &mut Formatter, road: String) {
-        if let Some(right) = &self.right {
-            right.print_node(f, road.clone() + "u");
-        }
+impl<T: Ord + Clone> SplayNode<T> {
+    pub(super) fn value(&self) -> &T {
+        &self.value
+    }
 
-        write!(f, "   ").unwrap();
-        for (x, y) in road.chars().tuple_windows() {
-            if x != y {
-                write!(f, "│  ").unwrap();
-            } else {
-                write!(f, "   ").unwrap();
-            }
+    pub(super) fn min(&self) -> &T {
+        let mut node = self;
+        while let Some(left) = node.left.as_deref() {
+            node = left;
         }
-        if let Some(last) = road.chars().last() {
-            if last == 'u' {
-                write!(f, "╭──").unwrap();
-            } else {
-                write!(f, "╰──").unwrap();
-            }
+        &node.value
+    }
+
+    pub(super) fn max(&self) -> &T {
+        let mut node = self;
+        while let Some(right) = node.right.as_deref() {
+            node = right;
         }
-        writeln!(f, "{:?}", &self.value).unwrap();
+        &node.value
+    }
 
-        if let Some(left) = &self.left {
-            left.print_node(f, road + "d");
+    pub(super) fn take_children(&mut self) -> [Option<Box<Self>>; 2] {
+        [self.left.take(), self.right.take()]
+    }
+
+    // the next n nodes as a balanced subtree, the middle one at its root
+    pub(super) fn build(nodes: &mut impl Iterator<Item = Box<Self>>, n: usize) -> Option<Box<Self>> {
+        if n == 0 {
+            return None;
         }
+        let left = Self::build(nodes, (n - 1) / 2);
+        let mut node = nodes.next().unwrap();
+        node.left = left;
+        node.right = Self::build(nodes, n / 2);
+        Some(node)
+    }
+}
+
+impl<T: Ord + Clone> NodeView for &SplayNode<T> {
+    fn left(&self) -> Option<Self> {
+        self.left.as_deref()
+    }
+
+    fn right(&self) -> Option<Self> {
+        self.right.as_deref()
+    }
+
+    fn id(&self) -> usize {
+        *self as *const SplayNode<T> as usize
+    }
+}
+
+impl<T: Ord + Clone + Debug> DotNode for &SplayNode<T> {
+    fn key(&self) -> String {
+        format!("{:?}", self.value)
     }
-}
\ No newline at end of file
+}
+
+impl<T: Ord + Clone> CheckedNode for &SplayNode<T> {
+    fn cmp_key(&self, other: &Self) -> Ordering {
+        self.value.cmp(&other.value)
+    }
+}
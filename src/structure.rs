@@ -0,0 +1,223 @@
+use std::cell::Cell;
+use std::cmp::Ordering;
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use rbtree::RBTree;
+use serde::{Deserialize, Serialize};
+
+use crate::experiment::Stats;
+use crate::shape::Shape;
+use crate::{bin_tree, rb_tree, splay_tree};
+
+// common interface the experiment harness drives the trees through
+pub trait Structure {
+    fn insert(&mut self, key: usize, stats: &mut Stats);
+
+    fn delete(&mut self, key: usize, stats: &mut Stats);
+
+    // None for the library structures, which keep their shape to themselves
+    fn height(&self) -> Option<usize>;
+
+    fn shape(&self) -> Option<Shape>;
+}
+
+impl Structure for bin_tree::BinTree<usize> {
+    fn insert(&mut self, key: usize, stats: &mut Stats) {
+        bin_tree::BinTree::insert(self, key, stats);
+    }
+
+    fn delete(&mut self, key: usize, stats: &mut Stats) {
+        bin_tree::BinTree::delete(self, key, stats);
+    }
+
+    fn height(&self) -> Option<usize> {
+        Some(bin_tree::BinTree::height(self))
+    }
+
+    fn shape(&self) -> Option<Shape> {
+        Some(bin_tree::BinTree::shape(self))
+    }
+}
+
+impl Structure for rb_tree::BinTree<usize> {
+    fn insert(&mut self, key: usize, stats: &mut Stats) {
+        rb_tree::BinTree::insert(self, key, stats);
+    }
+
+    fn delete(&mut self, key: usize, stats: &mut Stats) {
+        rb_tree::BinTree::delete(self, key, stats);
+    }
+
+    fn height(&self) -> Option<usize> {
+        Some(rb_tree::BinTree::height(self))
+    }
+
+    fn shape(&self) -> Option<Shape> {
+        Some(rb_tree::BinTree::shape(self))
+    }
+}
+
+impl Structure for splay_tree::SplayTree<usize> {
+    fn insert(&mut self, key: usize, stats: &mut Stats) {
+        splay_tree::SplayTree::insert(self, key, stats);
+    }
+
+    fn delete(&mut self, key: usize, stats: &mut Stats) {
+        splay_tree::SplayTree::delete(self, key, stats);
+    }
+
+    fn height(&self) -> Option<usize> {
+        Some(splay_tree::SplayTree::height(self) as usize)
+    }
+
+    fn shape(&self) -> Option<Shape> {
+        Some(splay_tree::SplayTree::shape(self))
+    }
+}
+
+thread_local! {
+    static COMPARISONS: Cell<usize> = const { Cell::new(0) };
+}
+
+// key wrapper counting comparisons made inside structures we can't instrument
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Counted(pub usize);
+
+impl Ord for Counted {
+    fn cmp(&self, other: &Self) -> Ordering {
+        COMPARISONS.with(|c| c.set(c.get() + 1));
+        self.0.cmp(&other.0)
+    }
+}
+
+impl PartialOrd for Counted {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// runs op and moves the comparisons it made into stats, pointer counts stay at zero
+fn counted<R>(stats: &mut Stats, op: impl FnOnce() -> R) -> R {
+    COMPARISONS.with(|c| c.set(0));
+    let result = op();
+    stats.comps(COMPARISONS.with(|c| c.get()));
+    result
+}
+
+// a multiset of how many times each key is in, a set would drop the repeats our trees keep;
+// entry finds the key in one search whether it is counted up, down or out
+impl Structure for BTreeMap<Counted, usize> {
+    fn insert(&mut self, key: usize, stats: &mut Stats) {
+        counted(stats, || *self.entry(Counted(key)).or_insert(0) += 1);
+    }
+
+    fn delete(&mut self, key: usize, stats: &mut Stats) {
+        counted(stats, || match self.entry(Counted(key)) {
+            Entry::Occupied(mut entry) if *entry.get() > 1 => *entry.get_mut() -= 1,
+            Entry::Occupied(entry) => {
+                entry.remove();
+            }
+            Entry::Vacant(_) => {}
+        });
+    }
+
+    fn height(&self) -> Option<usize> {
+        None
+    }
+
+    fn shape(&self) -> Option<Shape> {
+        None
+    }
+}
+
+impl Structure for RBTree<Counted, ()> {
+    fn insert(&mut self, key: usize, stats: &mut Stats) {
+        counted(stats, || RBTree::insert(self, Counted(key), ()));
+    }
+
+    fn delete(&mut self, key: usize, stats: &mut Stats) {
+        counted(stats, || RBTree::remove(self, &Counted(key)));
+    }
+
+    fn height(&self) -> Option<usize> {
+        None
+    }
+
+    fn shape(&self) -> Option<Shape> {
+        None
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StructureKind {
+    BinTree,
+    RbTree,
+    SplayTree,
+    // std::collections::BTreeMap counting repeats, a BTreeSet that keeps them, for reference
+    #[serde(rename = "btree_set")]
+    BTreeSet,
+    // the red-black tree from the rbtree crate, for reference
+    #[serde(rename = "rbtree_crate")]
+    RbTreeCrate,
+}
+
+impl StructureKind {
+    pub const ALL: [StructureKind; 5] = [
+        StructureKind::BinTree,
+        StructureKind::RbTree,
+        StructureKind::SplayTree,
+        StructureKind::BTreeSet,
+        StructureKind::RbTreeCrate,
+    ];
+
+    pub const TREES: [StructureKind; 3] = [
+        StructureKind::BinTree,
+        StructureKind::RbTree,
+        StructureKind::SplayTree,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            StructureKind::BinTree => "bin_tree",
+            StructureKind::RbTree => "rb_tree",
+            StructureKind::SplayTree => "splay_tree",
+            StructureKind::BTreeSet => "btree_set",
+            StructureKind::RbTreeCrate => "rbtree_crate",
+        }
+    }
+
+    // one of ours, as opposed to the reference structures from libraries
+    pub fn is_tree(&self) -> bool {
+        StructureKind::TREES.contains(self)
+    }
+
+    pub fn build(&self) -> Box<dyn Structure> {
+        match self {
+            StructureKind::BinTree => Box::new(bin_tree::BinTree::new()),
+            StructureKind::RbTree => Box::new(rb_tree::BinTree::new()),
+            StructureKind::SplayTree => Box::new(splay_tree::SplayTree::new()),
+            StructureKind::BTreeSet => Box::new(BTreeMap::<Counted, usize>::new()),
+            StructureKind::RbTreeCrate => Box::new(RBTree::<Counted, ()>::new()),
+        }
+    }
+}
+
+impl Display for StructureKind {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for StructureKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        StructureKind::ALL.into_iter()
+            .find(|kind| kind.name() == s)
+            .ok_or_else(|| format!("unknown structure `{}`", s))
+    }
+}
@@ -0,0 +1,165 @@
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+
+use crate::dot::DotNode;
+use crate::shape::NodeView;
+
+// what the validator needs beyond the shape: the keys themselves, for the ordering
+pub trait CheckedNode: NodeView {
+    fn cmp_key(&self, other: &Self) -> Ordering;
+}
+
+// keys are written with Debug
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum InvariantError {
+    // reached a second time, the links form a cycle or share a subtree
+    Revisited { key: String },
+    Order { key: String, previous: String },
+    Parent { key: String, expected: String, found: Option<String> },
+    Size { recorded: usize, counted: usize },
+    RedRoot { key: String },
+    RedRed { key: String, parent: String },
+    BlackHeight { key: String, left: usize, right: usize },
+    StrayNil { key: String },
+}
+
+impl Display for InvariantError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            InvariantError::Revisited { key } => write!(f, "{} is reachable more than once", key),
+            InvariantError::Order { key, previous } => write!(f, "{} comes after {} in order", key, previous),
+            InvariantError::Parent { key, expected, found } => match found {
+                Some(found) => write!(f, "parent of {} is {}, expected {}", key, found, expected),
+                None => write!(f, "parent of {} is unset, expected {}", key, expected),
+            },
+            InvariantError::Size { recorded, counted } => write!(f, "size is {} but the tree holds {} nodes", recorded, counted),
+            InvariantError::RedRoot { key } => write!(f, "root {} is red", key),
+            InvariantError::RedRed { key, parent } => write!(f, "red {} is a child of red {}", key, parent),
+            InvariantError::BlackHeight { key, left, right } => {
+                write!(f, "black height under {} is {} on the left and {} on the right", key, left, right)
+            }
+            InvariantError::StrayNil { key } => write!(f, "nil sentinel {} is still linked into the tree", key),
+        }
+    }
+}
+
+impl std::error::Error for InvariantError {}
+
+struct Entry<N> {
+    node: N,
+    parent: Option<usize>,
+    left: Option<usize>,
+    right: Option<usize>,
+}
+
+// size is the count the tree keeps, nil the id of its sentinel, None when it has neither;
+// the root of a tree with parent pointers has to point at itself
+pub fn validate<N: CheckedNode + DotNode>(root: Option<N>, size: Option<usize>, nil: Option<usize>) -> Result<(), InvariantError> {
+    validate_with(root, size, nil, N::key)
+}
+
+// validate for keys without Debug, the errors name nodes by what key makes of them
+pub(crate) fn validate_with<N: CheckedNode>(
+    root: Option<N>,
+    size: Option<usize>,
+    nil: Option<usize>,
+    key: impl Fn(&N) -> String,
+) -> Result<(), InvariantError> {
+    // pre-order on an explicit stack, a degenerate tree is as deep as it is large
+    let mut entries: Vec<Entry<N>> = Vec::new();
+    let mut seen = HashSet::new();
+    let mut stack = root.into_iter().map(|node| (node, None)).collect::<Vec<(N, Option<(usize, bool)>)>>();
+    while let Some((node, parent)) = stack.pop() {
+        if !seen.insert(node.id()) {
+            return Err(InvariantError::Revisited { key: key(&node) });
+        }
+        let index = entries.len();
+        if let Some((parent, is_left)) = parent {
+            let parent = &mut entries[parent];
+            if is_left { parent.left = Some(index) } else { parent.right = Some(index) }
+        }
+        stack.extend(node.right().map(|right| (right, Some((index, false)))));
+        stack.extend(node.left().map(|left| (left, Some((index, true)))));
+        entries.push(Entry { node, parent: parent.map(|(parent, _)| parent), left: None, right: None });
+    }
+
+    if let Some(size) = size {
+        if size != entries.len() {
+            return Err(InvariantError::Size { recorded: size, counted: entries.len() });
+        }
+    }
+    if let Some(nil) = nil {
+        if let Some(entry) = entries.iter().find(|entry| entry.node.id() == nil) {
+            return Err(InvariantError::StrayNil { key: key(&entry.node) });
+        }
+    }
+    check_parents(&entries, &key)?;
+    check_order(&entries, &key)?;
+    if entries.first().is_some_and(|root| root.node.red().is_some()) {
+        check_colors(&entries, &key)?;
+    }
+    Ok(())
+}
+
+fn check_parents<N: CheckedNode>(entries: &[Entry<N>], key: &impl Fn(&N) -> String) -> Result<(), InvariantError> {
+    for (index, entry) in entries.iter().enumerate() {
+        let Some(found) = entry.node.parent() else { continue };
+        let expected = &entries[entry.parent.unwrap_or(index)].node;
+        if found.as_ref().is_none_or(|found| found.id() != expected.id()) {
+            return Err(InvariantError::Parent {
+                key: key(&entry.node),
+                expected: key(expected),
+                found: found.map(|found| key(&found)),
+            });
+        }
+    }
+    Ok(())
+}
+
+// in-order the keys never go down, equal keys may sit on either side after rotations
+fn check_order<N: CheckedNode>(entries: &[Entry<N>], key: &impl Fn(&N) -> String) -> Result<(), InvariantError> {
+    let mut previous: Option<usize> = None;
+    let mut stack = Vec::new();
+    let mut current = (!entries.is_empty()).then_some(0);
+    while current.is_some() || !stack.is_empty() {
+        while let Some(index) = current {
+            stack.push(index);
+            current = entries[index].left;
+        }
+        let index = stack.pop().unwrap();
+        if let Some(previous) = previous {
+            let (node, before) = (&entries[index].node, &entries[previous].node);
+            if node.cmp_key(before).is_lt() {
+                return Err(InvariantError::Order { key: key(node), previous: key(before) });
+            }
+        }
+        previous = Some(index);
+        current = entries[index].right;
+    }
+    Ok(())
+}
+
+fn check_colors<N: CheckedNode>(entries: &[Entry<N>], key: &impl Fn(&N) -> String) -> Result<(), InvariantError> {
+    let red = |index: usize| entries[index].node.red() == Some(true);
+    if red(0) {
+        return Err(InvariantError::RedRoot { key: key(&entries[0].node) });
+    }
+    // black nodes below each node down to an empty link, backwards so children come first
+    let mut black_height = vec![0; entries.len()];
+    for index in (0..entries.len()).rev() {
+        let entry = &entries[index];
+        if let Some(parent) = entry.parent {
+            if red(index) && red(parent) {
+                return Err(InvariantError::RedRed { key: key(&entry.node), parent: key(&entries[parent].node) });
+            }
+        }
+        let below = |child: Option<usize>| child.map_or(0, |child| black_height[child] + usize::from(!red(child)));
+        let (left, right) = (below(entry.left), below(entry.right));
+        if left != right {
+            return Err(InvariantError::BlackHeight { key: key(&entry.node), left, right });
+        }
+        black_height[index] = left;
+    }
+    Ok(())
+}
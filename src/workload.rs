@@ -0,0 +1,89 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use rand::distr::{Distribution, Uniform};
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use rand_pcg::Pcg64;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Workload {
+    // n random keys from 0..2n-1, then all of them deleted in random order
+    Random,
+    // n random keys inserted, then n freshly drawn keys deleted (some of them miss)
+    RandomMisses,
+    // keys 0..n inserted in order, then deleted in random order
+    Increasing,
+}
+
+// keys of one run, inserted first and then deleted
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Sequence {
+    pub inserts: Vec<usize>,
+    pub deletes: Vec<usize>,
+}
+
+impl Workload {
+    pub const ALL: [Workload; 3] = [Workload::Random, Workload::RandomMisses, Workload::Increasing];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Workload::Random => "random",
+            Workload::RandomMisses => "random_misses",
+            Workload::Increasing => "increasing",
+        }
+    }
+
+    pub fn generate(&self, n: usize, seed: u64) -> Sequence {
+        let rng = &mut Pcg64::seed_from_u64(seed);
+
+        let inserts = match self {
+            Workload::Random | Workload::RandomMisses => {
+                let range = Uniform::new(0, 2 * n - 1).unwrap();
+                (0..n).map(|_| range.sample(rng)).collect::<Vec<_>>()
+            }
+            Workload::Increasing => (0..n).collect(),
+        };
+
+        let deletes = match self {
+            Workload::RandomMisses => {
+                let range = Uniform::new(0, 2 * n - 1).unwrap();
+                (0..n).map(|_| range.sample(rng)).collect()
+            }
+            Workload::Random | Workload::Increasing => {
+                let mut deletes = inserts.clone();
+                deletes.shuffle(rng);
+                deletes
+            }
+        };
+
+        Sequence { inserts, deletes }
+    }
+}
+
+impl Display for Workload {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Workload {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Workload::ALL.into_iter()
+            .find(|workload| workload.name() == s)
+            .ok_or_else(|| format!("unknown workload `{}`", s))
+    }
+}
+
+// every (n, rep) cell gets its own seed so a cell can be rerun on its own
+pub fn cell_seed(seed: u64, n: usize, rep: usize) -> u64 {
+    let mut x = seed ^ (n as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15) ^ (rep as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F);
+    // splitmix64 finalizer
+    x = (x ^ (x >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    x ^ (x >> 31)
+}